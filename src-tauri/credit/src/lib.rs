@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 
 declare_id!("98hRPJ2D9Dmuaa5DSm1qF2WZC6U5tJgkcGZgRHzR9SAC");
 
@@ -14,6 +15,8 @@ pub mod credit {
         credit_rating: String,
         credit_limit: String,
         risk_level: String,
+        scorecard: ScorecardRef,
+        provenance: ProvenanceRef,
     ) -> Result<()> {
         let company = &mut ctx.accounts.company;
 
@@ -25,6 +28,9 @@ pub mod credit {
         company.risk_level = risk_level;
         company.authority = ctx.accounts.authority.key();
         company.timestamp = Clock::get()?.unix_timestamp;
+        company.version = Company::VERSION;
+        company.scorecard = scorecard;
+        company.provenance = provenance;
 
         Ok(())
     }
@@ -35,8 +41,11 @@ pub mod credit {
         credit_rating: String,
         credit_limit: String,
         risk_level: String,
+        scorecard: ScorecardRef,
+        provenance: ProvenanceRef,
     ) -> Result<()> {
-        let company = &mut ctx.accounts.company;
+        let info = ctx.accounts.company.to_account_info();
+        let mut company = Company::load(&info.try_borrow_data()?)?;
 
         require_keys_eq!(
             company.authority,
@@ -49,19 +58,55 @@ pub mod credit {
         company.credit_limit = credit_limit;
         company.risk_level = risk_level;
         company.timestamp = Clock::get()?.unix_timestamp;
+        company.version = Company::VERSION;
+        company.scorecard = scorecard;
+        company.provenance = provenance;
+
+        // 旧版账户空间不足,由 authority 补足租金后扩容
+        let space = 8 + Company::MAX_SIZE;
+        if info.data_len() < space {
+            let rent = Rent::get()?
+                .minimum_balance(space)
+                .saturating_sub(info.lamports());
+            if rent > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.authority.to_account_info(),
+                            to: info.clone(),
+                        },
+                    ),
+                    rent,
+                )?;
+            }
+            info.resize(space)?;
+        }
+        let mut data = info.try_borrow_mut_data()?;
+        company.try_serialize(&mut &mut data[..])?;
 
         Ok(())
     }
 
     pub fn delete_company(ctx: Context<DeleteCompany>) -> Result<()> {
-        let company = &mut ctx.accounts.company;
+        let info = ctx.accounts.company.to_account_info();
+        let company = Company::load(&info.try_borrow_data()?)?;
         require_keys_eq!(
             company.authority,
             ctx.accounts.authority.key(),
             CreditError::Unauthorized
         );
-        let authority = &mut ctx.accounts.authority;
-        company.close(authority.to_account_info())?;
+
+        // 旧版账户无法按当前布局反序列化,手动关闭
+        let authority = ctx.accounts.authority.to_account_info();
+        let lamports = authority
+            .lamports()
+            .checked_add(info.lamports())
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **authority.try_borrow_mut_lamports()? = lamports;
+        **info.try_borrow_mut_lamports()? = 0;
+        info.assign(&system_program::ID);
+        info.resize(0)?;
         Ok(())
     }
 }
@@ -84,19 +129,25 @@ pub struct InitializeCompany<'info> {
 
 #[derive(Accounts)]
 pub struct UpdateCompany<'info> {
+    /// CHECK: 可能是旧版布局,由 Company::load 校验类型后迁移到当前版本
+    #[account(mut, owner = crate::ID)]
+    pub company: UncheckedAccount<'info>,
     #[account(mut)]
-    pub company: Account<'info, Company>,
     pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct DeleteCompany<'info> {
-    #[account(mut, close = authority)]
-    pub company: Account<'info, Company>,
+    /// CHECK: 可能是旧版布局,由 Company::load 校验类型
+    #[account(mut, owner = crate::ID)]
+    pub company: UncheckedAccount<'info>,
     #[account(mut)]
     pub authority: Signer<'info>,
 }
 
+/// 企业信用账户。版本 0 的账户只有 timestamp 及之前的字段,
+/// 之后新增的字段追加在 version 之后,旧版账户在 update_company 时迁移
 #[account]
 pub struct Company {
     pub company_id: String,
@@ -107,10 +158,26 @@ pub struct Company {
     pub risk_level: String,
     pub authority: Pubkey,
     pub timestamp: i64,
+    pub version: u8,
+    pub scorecard: ScorecardRef,
+    pub provenance: ProvenanceRef,
 }
 
-impl Company {
-    pub const MAX_SIZE: usize = 4 + 64 +   // company_id
+/// 版本 0 的企业账户布局
+#[derive(AnchorDeserialize)]
+struct CompanyV0 {
+    company_id: String,
+    company_name: String,
+    credit_score: u32,
+    credit_rating: String,
+    credit_limit: String,
+    risk_level: String,
+    authority: Pubkey,
+    timestamp: i64,
+}
+
+impl CompanyV0 {
+    const MAX_SIZE: usize = 4 + 64 +   // company_id
         4 + 64 +   // company_name
         4 +        // credit_score (u32)
         4 + 16 +   // credit_rating
@@ -120,6 +187,80 @@ impl Company {
         8; // timestamp (i64)
 }
 
+/// 生成评分所用的评分卡
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct ScorecardRef {
+    pub name: String,
+    pub version: String,
+    pub hash: String,
+}
+
+impl ScorecardRef {
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const MAX_VERSION_LEN: usize = 16;
+
+    pub const MAX_SIZE: usize = 4 + ScorecardRef::MAX_NAME_LEN + // name
+        4 + ScorecardRef::MAX_VERSION_LEN + // version
+        4 + 64; // hash (SHA-256 hex)
+}
+
+/// 评分所用的源数据位置
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct ProvenanceRef {
+    pub file_name: String,
+    pub file_hash: String,
+    pub sheet_name: String,
+    pub row: u32,
+    pub imported_at: i64,
+}
+
+impl ProvenanceRef {
+    pub const MAX_SIZE: usize = 4 + 128 + // file_name
+        4 + 64 + // file_hash (SHA-256 hex)
+        4 + 96 + // sheet_name
+        4 + // row (u32)
+        8; // imported_at (i64)
+}
+
+impl Company {
+    pub const VERSION: u8 = 1;
+
+    pub const MAX_SIZE: usize = CompanyV0::MAX_SIZE +
+        1 +        // version (u8)
+        ScorecardRef::MAX_SIZE +
+        ProvenanceRef::MAX_SIZE;
+
+    /// 数据是否为企业账户
+    pub fn is_company(data: &[u8]) -> bool {
+        data.starts_with(Company::DISCRIMINATOR)
+    }
+
+    /// 按账户版本读取企业账户数据(含类型标识)。版本 0 的账户按创建时的空间分配,
+    /// 小于当前版本所需空间;其新增字段取默认值
+    pub fn load(data: &[u8]) -> Result<Self> {
+        if !Company::is_company(data) {
+            return err!(ErrorCode::AccountDiscriminatorMismatch);
+        }
+        if data.len() >= 8 + Company::MAX_SIZE {
+            return Company::try_deserialize(&mut &data[..]);
+        }
+        let v0 = CompanyV0::deserialize(&mut &data[Company::DISCRIMINATOR.len()..])?;
+        Ok(Company {
+            company_id: v0.company_id,
+            company_name: v0.company_name,
+            credit_score: v0.credit_score,
+            credit_rating: v0.credit_rating,
+            credit_limit: v0.credit_limit,
+            risk_level: v0.risk_level,
+            authority: v0.authority,
+            timestamp: v0.timestamp,
+            version: 0,
+            scorecard: ScorecardRef::default(),
+            provenance: ProvenanceRef::default(),
+        })
+    }
+}
+
 /// 自定义错误
 #[error_code]
 pub enum CreditError {
@@ -1,72 +1,155 @@
+mod backtest;
+mod batch;
 mod calc;
+mod cell;
+mod consistency;
+mod distress;
+mod ecl;
+mod formats;
+mod history;
+mod mapping;
+mod numfmt;
+mod pd;
+mod peer;
+mod portfolio;
+mod reader;
+mod scorecard;
+mod simulate;
+mod store;
+mod stress;
+mod template;
+mod timeline;
 mod types;
+mod units;
+mod validate;
 
 use std::collections::HashMap;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
 use std::time::Duration;
 
-use crate::excel::calc::{extract_companies_from_excel, parse_credit_limit};
-use crate::excel::types::{CompanyData, CompanyWithScoreEn, ExcelResult};
-use crate::excel::{calc::process_excel_internal, types::ExcelResultEn};
-use rust_xlsxwriter::{Format, FormatAlign, Workbook};
-use tokio::sync::Mutex;
+use crate::excel::batch::ImportTask;
+use crate::excel::calc::{extract_companies_from_excel, score_company, suggested_exposure};
+use crate::excel::consistency::ConsistencyRule;
+use crate::excel::ecl::EclConfig;
+use crate::excel::scorecard::{Scorecard, ScorecardInfo};
+use crate::excel::types::{
+    BacktestResult, BankRecord, BankSubmission, CompanyData, CompanyDataEn, CompanyWithScoreEn,
+    EclResult, ExcelResult, ExcelResultEn, ImportRecord, PdCalibration, PortfolioSummary,
+    RatingMigration, ScoreHistoryEntry, SensitivityResult, SheetMapping, SourceMapping,
+    StressScenario, StressTestResult, Unit, WhatIfResult,
+};
+use crate::excel::{calc::process_excel_batch, types::ImportResult};
+use rust_xlsxwriter::{ExcelDateTime, Format, FormatAlign, Workbook};
+use tauri::Emitter;
 
-static BANK_LIMIT_DB: LazyLock<Mutex<HashMap<String, f64>>> =
-    LazyLock::new(|| Mutex::new(HashMap::new()));
+pub use crate::excel::consistency::init_consistency_rules;
+pub use crate::excel::ecl::init_ecl_config;
+pub use crate::excel::mapping::init_header_mappings;
+pub use crate::excel::pd::init_pd_model;
+pub use crate::excel::scorecard::init_scorecards;
+pub use crate::excel::store::init_store;
+pub use crate::excel::types::{Provenance, ScorecardId};
 
-#[tauri::command]
-pub fn process_excel(paths: Vec<String>) -> Result<Vec<ExcelResultEn>, String> {
-    let results_cn = process_excel_internal(paths)?;
-    Ok(results_cn.into_iter().map(|r| r.into()).collect())
-}
-#[tauri::command]
-pub async fn generate_template_excel(
-    file_path: String,
-    headers: Vec<String>,
-    template_row: Option<Vec<String>>,
-) -> Result<(), String> {
-    let mut workbook = Workbook::new();
-    let worksheet = workbook.add_worksheet();
+/// 授信申请的审批键:(企业ID, 评分记录 ID)
+type ApprovalKey = (String, Option<i64>);
+type ApprovalGate = Arc<tokio::sync::Mutex<()>>;
 
-    let center_format = Format::new()
-        .set_align(rust_xlsxwriter::FormatAlign::Center)
-        .set_align(rust_xlsxwriter::FormatAlign::VerticalCenter);
+/// 按审批键排队的授信申请,同一次评分的申请依次处理,后到的直接复用先前的批复
+static APPROVAL_GATES: LazyLock<Mutex<HashMap<ApprovalKey, ApprovalGate>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
 
-    let header_format = Format::new()
-        .set_align(rust_xlsxwriter::FormatAlign::Center)
-        .set_align(rust_xlsxwriter::FormatAlign::VerticalCenter)
-        .set_bold();
+/// 持有审批键的处理权,结束(包括出错)时若无其他申请排队则移除该键
+struct PendingApproval {
+    key: ApprovalKey,
+    _guard: tokio::sync::OwnedMutexGuard<()>,
+}
 
-    for (col, header) in headers.iter().enumerate() {
-        worksheet
-            .write_string_with_format(0, col as u16, header, &header_format)
-            .map_err(|e| e.to_string())?;
+impl PendingApproval {
+    /// 等待同一审批键上进行中的申请结束
+    async fn acquire(key: ApprovalKey) -> Self {
+        let gate = APPROVAL_GATES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        PendingApproval {
+            key,
+            _guard: gate.lock_owned().await,
+        }
     }
+}
 
-    if let Some(template_data) = template_row {
-        let data_count = template_data.len().min(headers.len());
-        for (col, value) in template_data.iter().take(data_count).enumerate() {
-            if let Ok(num) = value.parse::<f64>() {
-                worksheet
-                    .write_number_with_format(1, col as u16, num, &center_format)
-                    .map_err(|e| e.to_string())?;
-            } else {
-                worksheet
-                    .write_string_with_format(1, col as u16, value, &center_format)
-                    .map_err(|e| e.to_string())?;
-            }
+impl Drop for PendingApproval {
+    fn drop(&mut self) {
+        let mut gates = APPROVAL_GATES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // 仅剩表中和本申请持有的引用时,没有排队的申请
+        if gates
+            .get(&self.key)
+            .is_some_and(|gate| Arc::strong_count(gate) == 2)
+        {
+            gates.remove(&self.key);
         }
     }
+}
 
-    for col in 0..headers.len() {
-        worksheet
-            .set_column_width(col as u16, 15.0)
-            .map_err(|e| e.to_string())?;
-    }
+/// 在阻塞线程中访问本地数据库,避免占用异步运行时的工作线程
+async fn with_store_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("数据库任务异常: {}", e))?
+}
 
-    workbook.save(&file_path).map_err(|e| e.to_string())?;
+fn round2(x: f64) -> f64 {
+    (x * 100.0).round() / 100.0
+}
 
-    Ok(())
+/// 在后台线程读取并评分,通过 import_progress 事件推送进度,可用 cancel_import 取消。
+/// 评分结果保存到本地数据库
+#[tauri::command]
+pub async fn process_excel(
+    paths: Vec<String>,
+    source: Option<String>,
+    window: tauri::Window,
+) -> Result<ImportResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let task = ImportTask::start(paths.len(), move |progress| {
+            let _ = window.emit("import_progress", progress);
+        });
+        let (results_cn, diagnostics) = process_excel_batch(paths, source.as_deref(), &task)?;
+        let import_id = if results_cn.is_empty() {
+            None
+        } else {
+            Some(store::record_import(&results_cn, source.as_deref())?)
+        };
+        Ok(ImportResult {
+            import_id,
+            results: results_cn.into_iter().map(|r| r.into()).collect(),
+            diagnostics,
+        })
+    })
+    .await
+    .map_err(|e| format!("导入任务异常: {}", e))?
+}
+
+/// 取消正在进行的导入,没有进行中的导入时返回 false
+#[tauri::command]
+pub fn cancel_import() -> bool {
+    batch::cancel_import()
+}
+/// 生成带下拉列表、数值校验、字段批注和填写说明的导入模板
+#[tauri::command]
+pub async fn generate_template_excel(
+    file_path: String,
+    headers: Vec<String>,
+    template_row: Option<Vec<String>>,
+) -> Result<(), String> {
+    let industries = scorecard::active_scorecard().industries();
+    template::write_template(&file_path, &headers, template_row.as_deref(), &industries)
 }
 #[tauri::command]
 pub async fn generate_single_report(
@@ -128,7 +211,58 @@ pub async fn generate_single_report(
     worksheet
         .write_string_with_format(row, 1, &company.risk_level, &value_format)
         .map_err(|e| e.to_string())?;
-    row += 2;
+    row += 1;
+    worksheet
+        .write_string_with_format(row, 0, "评分卡", &key_format)
+        .map_err(|e| e.to_string())?;
+    worksheet
+        .write_string_with_format(
+            row,
+            1,
+            format!("{} v{}", company.scorecard.name, company.scorecard.version),
+            &value_format,
+        )
+        .map_err(|e| e.to_string())?;
+    row += 1;
+    worksheet
+        .write_string_with_format(row, 0, "评分卡哈希", &key_format)
+        .map_err(|e| e.to_string())?;
+    worksheet
+        .write_string_with_format(row, 1, &company.scorecard.hash, &value_format)
+        .map_err(|e| e.to_string())?;
+    row += 1;
+    if let Some(provenance) = &company.provenance {
+        let items = [
+            ("来源文件", provenance.file.clone()),
+            ("文件哈希", provenance.file_hash.clone()),
+            ("工作表", provenance.sheet_name.clone()),
+            ("行号", provenance.row.to_string()),
+        ];
+        for (key, value) in items {
+            worksheet
+                .write_string_with_format(row, 0, key, &key_format)
+                .map_err(|e| e.to_string())?;
+            worksheet
+                .write_string_with_format(row, 1, value, &value_format)
+                .map_err(|e| e.to_string())?;
+            row += 1;
+        }
+        let imported_at =
+            ExcelDateTime::from_timestamp(provenance.imported_at).map_err(|e| e.to_string())?;
+        worksheet
+            .write_string_with_format(row, 0, "导入时间(UTC)", &key_format)
+            .map_err(|e| e.to_string())?;
+        worksheet
+            .write_datetime_with_format(
+                row,
+                1,
+                &imported_at,
+                &value_format.clone().set_num_format("yyyy-mm-dd hh:mm:ss"),
+            )
+            .map_err(|e| e.to_string())?;
+        row += 1;
+    }
+    row += 1;
 
     worksheet
         .write_string_with_format(row, 0, "各项得分详情", &header_format)
@@ -183,8 +317,166 @@ pub async fn generate_single_report(
             &value_format,
         )
         .map_err(|e| e.to_string())?;
+    row += 1;
+    worksheet
+        .write_string_with_format(row, 0, "趋势调整分", &key_format)
+        .map_err(|e| e.to_string())?;
+    worksheet
+        .write_number_with_format(
+            row,
+            1,
+            company.score_details.trend_adjustment,
+            &value_format,
+        )
+        .map_err(|e| e.to_string())?;
     row += 2;
 
+    worksheet
+        .write_string_with_format(row, 0, "评分因子明细", &header_format)
+        .map_err(|e| e.to_string())?;
+    row += 1;
+    for factor in &company.factor_details {
+        worksheet
+            .write_string_with_format(row, 0, &factor.name, &key_format)
+            .map_err(|e| e.to_string())?;
+        worksheet
+            .write_string_with_format(
+                row,
+                1,
+                format!(
+                    "{}  得分 {}/{}",
+                    factor.band,
+                    round2(factor.points),
+                    round2(factor.max_points)
+                ),
+                &value_format,
+            )
+            .map_err(|e| e.to_string())?;
+        row += 1;
+    }
+    row += 1;
+
+    if !company.reason_codes.is_empty() {
+        worksheet
+            .write_string_with_format(row, 0, "主要扣分原因", &header_format)
+            .map_err(|e| e.to_string())?;
+        row += 1;
+        for reason in &company.reason_codes {
+            worksheet
+                .write_string_with_format(row, 0, &reason.code, &key_format)
+                .map_err(|e| e.to_string())?;
+            worksheet
+                .write_string_with_format(
+                    row,
+                    1,
+                    format!("{}(失分 {})", reason.message, round2(reason.points_lost)),
+                    &value_format,
+                )
+                .map_err(|e| e.to_string())?;
+            row += 1;
+        }
+        row += 1;
+    }
+
+    if !company.warnings.is_empty() {
+        worksheet
+            .write_string_with_format(row, 0, "数据一致性提示", &header_format)
+            .map_err(|e| e.to_string())?;
+        row += 1;
+        for warning in &company.warnings {
+            worksheet
+                .write_string_with_format(
+                    row,
+                    0,
+                    if warning.corrected {
+                        "已修正"
+                    } else {
+                        "待核实"
+                    },
+                    &key_format,
+                )
+                .map_err(|e| e.to_string())?;
+            worksheet
+                .write_string_with_format(row, 1, &warning.message, &value_format)
+                .map_err(|e| e.to_string())?;
+            row += 1;
+        }
+        row += 1;
+    }
+
+    let distress = &company.distress;
+    let indicators = [
+        (
+            "Altman Z''",
+            distress
+                .altman_z
+                .zip(distress.altman_zone.as_ref())
+                .map(|(z, zone)| format!("{}({})", round2(z), zone)),
+        ),
+        (
+            "Ohlson O-score",
+            distress
+                .ohlson_o
+                .zip(distress.ohlson_probability)
+                .map(|(o, p)| format!("{}(违约概率 {:.2}%)", round2(o), p * 100.0)),
+        ),
+        (
+            "Zmijewski",
+            distress
+                .zmijewski
+                .zip(distress.zmijewski_probability)
+                .map(|(x, p)| format!("{}(违约概率 {:.2}%)", round2(x), p * 100.0)),
+        ),
+    ];
+    if indicators.iter().any(|(_, v)| v.is_some()) {
+        worksheet
+            .write_string_with_format(row, 0, "财务困境指标", &header_format)
+            .map_err(|e| e.to_string())?;
+        row += 1;
+        for (name, value) in indicators {
+            worksheet
+                .write_string_with_format(row, 0, name, &key_format)
+                .map_err(|e| e.to_string())?;
+            worksheet
+                .write_string_with_format(
+                    row,
+                    1,
+                    value.unwrap_or_else(|| "数据不足".into()),
+                    &value_format,
+                )
+                .map_err(|e| e.to_string())?;
+            row += 1;
+        }
+        row += 1;
+    }
+
+    if company.company_data.history.len() > 1 {
+        worksheet
+            .write_string_with_format(row, 0, "历年财务数据", &header_format)
+            .map_err(|e| e.to_string())?;
+        row += 1;
+        for period in &company.company_data.history {
+            worksheet
+                .write_string_with_format(row, 0, format!("{}年", period.fiscal_year), &key_format)
+                .map_err(|e| e.to_string())?;
+            worksheet
+                .write_string_with_format(
+                    row,
+                    1,
+                    format!(
+                        "营业收入 {} 万元,净利润 {} 万元,资产负债率 {}%",
+                        round2(period.revenue),
+                        round2(period.net_profit),
+                        round2(period.debt_to_asset_ratio)
+                    ),
+                    &value_format,
+                )
+                .map_err(|e| e.to_string())?;
+            row += 1;
+        }
+        row += 1;
+    }
+
     worksheet
         .write_string_with_format(row, 0, "企业原始数据", &header_format)
         .map_err(|e| e.to_string())?;
@@ -214,6 +506,22 @@ pub async fn generate_single_report(
             &value_format,
         )
         .map_err(|e| e.to_string())?;
+    if !company.company_data.original_units.is_empty() {
+        row += 1;
+        let note = company
+            .company_data
+            .original_units
+            .iter()
+            .map(|(column, unit)| format!("{} 原始单位为{}", column, unit.label()))
+            .collect::<Vec<_>>()
+            .join(";");
+        worksheet
+            .write_string_with_format(row, 0, "金额单位", &key_format)
+            .map_err(|e| e.to_string())?;
+        worksheet
+            .write_string_with_format(row, 1, format!("{},已统一换算为万元", note), &value_format)
+            .map_err(|e| e.to_string())?;
+    }
 
     workbook.save(&file_path).map_err(|e| e.to_string())?;
 
@@ -237,6 +545,9 @@ pub fn get_company_by_id(
     }
     Ok(None)
 }
+/// 提交授信申请,scored_at 为评分时间(导入时间),用于找到对应的评分记录,
+/// 为空时取企业最近一次评分。同一次评分已有批复时直接返回该额度,
+/// 审批中的重复申请等待其结果;企业没有评分记录时沿用企业最近的批复
 #[tauri::command]
 pub async fn submit_to_bank(
     company_id: String,
@@ -245,21 +556,41 @@ pub async fn submit_to_bank(
     credit_rating: String,
     credit_limit: String,
     risk_level: String,
+    scored_at: Option<i64>,
 ) -> Result<f64, String> {
-    println!(
-        "[河北银行] 收到提交请求:公司ID={},名称={},评分={},评级={},额度={},风险等级={}",
-        company_id, company_name, credit_score, credit_rating, credit_limit, risk_level
-    );
-    let mut db = BANK_LIMIT_DB.lock().await;
-    if let Some(&limit) = db.get(&company_id) {
+    if company_id.trim().is_empty() {
+        return Err("企业ID 不能为空".into());
+    }
+    let id = company_id.clone();
+    let score_run_id = with_store_blocking(move || store::score_run_id(&id, scored_at)).await?;
+    let _pending = PendingApproval::acquire((company_id.clone(), score_run_id)).await;
+
+    let id = company_id.clone();
+    let existing = with_store_blocking(move || match score_run_id {
+        Some(run) => store::approval_for_run(run),
+        None => store::latest_approval(&id),
+    })
+    .await?;
+    if let Some(limit) = existing {
         return Ok(limit);
     }
 
+    let submission = BankSubmission {
+        company_id: company_id.clone(),
+        company_name,
+        credit_score,
+        credit_rating: credit_rating.clone(),
+        credit_limit: credit_limit.clone(),
+        risk_level: risk_level.clone(),
+    };
+    let submission_id =
+        with_store_blocking(move || store::record_submission(&submission, score_run_id)).await?;
+
     // 模拟银行审批延迟
     tokio::time::sleep(Duration::from_secs(2)).await;
 
-    // 解析额度字符串
-    let base_limit = parse_credit_limit(&credit_limit);
+    // 与组合分析相同的额度档位敞口,需要担保和拒绝授信的企业为 0
+    let base_limit = suggested_exposure(&credit_limit);
 
     // 根据风险等级和信用评分计算额度
     let risk_factor = match risk_level.as_str() {
@@ -280,18 +611,213 @@ pub async fn submit_to_bank(
 
     let approved_limit = (base_limit * risk_factor * score_factor * rating_factor).round();
 
-    db.insert(company_id.clone(), approved_limit);
-
-    println!(
-        "[河北银行] 批复完成:公司={},额度={} 万元",
-        company_name, approved_limit
-    );
-
+    with_store_blocking(move || store::record_approval(submission_id, &company_id, approved_limit))
+        .await?;
     Ok(approved_limit)
 }
 /// 获取公司批复额度
 #[tauri::command]
-pub async fn get_bank_credit_limit(company_id: String) -> Option<f64> {
-    let db = BANK_LIMIT_DB.lock().await;
-    db.get(&company_id).cloned()
+pub async fn get_bank_credit_limit(company_id: String) -> Result<Option<f64>, String> {
+    with_store_blocking(move || store::latest_approval(&company_id)).await
+}
+/// 获取公司的授信申请及批复记录
+#[tauri::command]
+pub fn get_bank_history(company_id: String) -> Result<Vec<BankRecord>, String> {
+    store::bank_history(&company_id)
+}
+/// 列出本地保存的导入记录,最近的在前
+#[tauri::command]
+pub fn list_imports() -> Result<Vec<ImportRecord>, String> {
+    store::list_imports()
+}
+/// 读取一次导入的评分结果
+#[tauri::command]
+pub fn load_import(import_id: i64) -> Result<Vec<ExcelResultEn>, String> {
+    Ok(store::load_import(import_id)?
+        .into_iter()
+        .map(|r| r.into())
+        .collect())
+}
+/// 获取企业的评分时间线,含每次评分的评级变动和输入变化
+#[tauri::command]
+pub fn get_score_history(company_id: String) -> Result<Vec<ScoreHistoryEntry>, String> {
+    timeline::score_history(&company_id)
+}
+/// 统计两个时点(Unix 时间戳,秒)之间的评级迁移矩阵
+#[tauri::command]
+pub fn get_rating_migration(from: i64, to: i64) -> Result<RatingMigration, String> {
+    timeline::rating_migration(from, to)
+}
+/// 汇总一次导入的组合指标,top_n 为最大敞口和最高风险名单的长度,默认 10
+#[tauri::command]
+pub fn analyze_portfolio(import_id: i64, top_n: Option<usize>) -> Result<PortfolioSummary, String> {
+    let results = store::load_import(import_id)?;
+    Ok(portfolio::analyze_portfolio(
+        import_id,
+        &results,
+        top_n.unwrap_or(10),
+    ))
+}
+/// 导出组合分析报告
+#[tauri::command]
+pub async fn generate_portfolio_report(
+    file_path: String,
+    summary: PortfolioSummary,
+) -> Result<(), String> {
+    portfolio::write_portfolio_report(&file_path, &summary)
+}
+/// 计算一次导入的预期信用损失,collateral 为 企业ID -> 担保方式
+#[tauri::command]
+pub fn calculate_ecl(
+    import_id: i64,
+    collateral: Option<HashMap<String, String>>,
+) -> Result<EclResult, String> {
+    let results = store::load_import(import_id)?;
+    ecl::calculate_ecl(
+        import_id,
+        &results,
+        &collateral.unwrap_or_default(),
+        &ecl::ecl_config(),
+    )
+}
+/// 获取当前的预期信用损失参数
+#[tauri::command]
+pub fn get_ecl_config() -> EclConfig {
+    ecl::ecl_config()
+}
+/// 保存预期信用损失参数
+#[tauri::command]
+pub fn save_ecl_config(config: EclConfig) -> Result<(), String> {
+    ecl::save_ecl_config(config)
+}
+/// 按压力情景对一次导入的企业数据施加冲击并重新评分
+#[tauri::command]
+pub fn run_stress_test(
+    import_id: i64,
+    scenarios: Vec<StressScenario>,
+) -> Result<StressTestResult, String> {
+    let results = store::load_import(import_id)?;
+    stress::run_stress_test(import_id, &results, &scenarios)
+}
+/// 导出压力测试报告
+#[tauri::command]
+pub async fn generate_stress_report(
+    file_path: String,
+    result: StressTestResult,
+) -> Result<(), String> {
+    stress::write_stress_report(&file_path, &result)
+}
+/// 列出已加载的评分卡
+#[tauri::command]
+pub fn list_scorecards() -> Vec<ScorecardInfo> {
+    scorecard::all_scorecards()
+}
+/// 从规则文件加载评分卡
+#[tauri::command]
+pub fn load_scorecard(path: String) -> Result<ScorecardInfo, String> {
+    scorecard::register_scorecard_file(&path)
+}
+/// 切换当前生效的评分卡
+#[tauri::command]
+pub fn switch_scorecard(name: String, version: String) -> Result<ScorecardInfo, String> {
+    scorecard::activate_scorecard(&name, &version)
+}
+/// 获取指定哈希的评分卡规则
+#[tauri::command]
+pub fn get_scorecard(hash: String) -> Result<Scorecard, String> {
+    scorecard::find_scorecard(&hash).map(|s| (*s).clone())
+}
+/// 使用指定哈希的评分卡重新评分,用于审计复现历史结果
+#[tauri::command]
+pub fn rescore_company(
+    company: CompanyDataEn,
+    scorecard_hash: String,
+) -> Result<CompanyWithScoreEn, String> {
+    let scorecard = scorecard::find_scorecard(&scorecard_hash)?;
+    Ok(score_company(company.into(), &scorecard).into())
+}
+/// 模拟调整企业数据后的评分变化
+#[tauri::command]
+pub fn simulate_what_if(
+    company: CompanyDataEn,
+    overrides: HashMap<String, serde_json::Value>,
+) -> Result<WhatIfResult, String> {
+    simulate::simulate_what_if(company.into(), &overrides, &scorecard::active_scorecard())
+}
+/// 分析提升一个信用评级所需的最小单项调整
+#[tauri::command]
+pub fn analyze_rating_sensitivity(company: CompanyDataEn) -> SensitivityResult {
+    simulate::analyze_rating_sensitivity(&company.into(), &scorecard::active_scorecard())
+}
+/// 使用带违约标志的历史数据拟合违约概率模型,source 为数据来源,用于匹配自定义列映射
+#[tauri::command]
+pub fn fit_pd_model(path: String, source: Option<String>) -> Result<PdCalibration, String> {
+    pd::train_pd_model(&path, source.as_deref())
+}
+/// 获取当前违约概率模型的校准统计
+#[tauri::command]
+pub fn get_pd_calibration() -> Option<PdCalibration> {
+    pd::current_model().map(|m| m.calibration)
+}
+/// 使用已知违约结果的样本回测当前评分卡,评分低于 cutoff 视为预测违约;
+/// source 为数据来源,用于匹配自定义列映射
+#[tauri::command]
+pub fn backtest_scorecard(
+    path: String,
+    cutoff: Option<f64>,
+    source: Option<String>,
+) -> Result<BacktestResult, String> {
+    let samples = pd::load_labeled_samples(&path, source.as_deref())?;
+    backtest::run_backtest(
+        &path,
+        &samples,
+        &scorecard::active_scorecard(),
+        cutoff.unwrap_or(60.0),
+    )
+}
+/// 导出回测报告
+#[tauri::command]
+pub async fn generate_backtest_report(
+    file_path: String,
+    result: BacktestResult,
+) -> Result<(), String> {
+    backtest::write_backtest_report(&file_path, &result)
+}
+/// 获取当前的跨字段一致性规则
+#[tauri::command]
+pub fn get_consistency_rules() -> Vec<ConsistencyRule> {
+    consistency::consistency_rules()
+}
+/// 保存跨字段一致性规则
+#[tauri::command]
+pub fn save_consistency_rules(rules: Vec<ConsistencyRule>) -> Result<(), String> {
+    consistency::save_consistency_rules(rules)
+}
+/// 导入前预览表头映射,source 为数据来源名称
+#[tauri::command]
+pub fn preview_header_mapping(
+    paths: Vec<String>,
+    source: Option<String>,
+) -> Result<Vec<SheetMapping>, String> {
+    mapping::preview_header_mapping(&paths, source.as_deref())
+}
+/// 获取所有数据来源的自定义列映射
+#[tauri::command]
+pub fn get_header_mappings() -> HashMap<String, SourceMapping> {
+    mapping::header_mappings()
+}
+/// 保存数据来源的自定义列映射(原始列名 -> 标准列名)及金额列单位声明
+#[tauri::command]
+pub fn save_header_mapping(
+    source: String,
+    columns: HashMap<String, String>,
+    units: Option<HashMap<String, Unit>>,
+) -> Result<(), String> {
+    mapping::save_header_mapping(
+        &source,
+        SourceMapping {
+            columns,
+            units: units.unwrap_or_default(),
+        },
+    )
 }
@@ -0,0 +1,363 @@
+use crate::excel::calc::{calculate_credit_score, get_credit_rating, RATING_GRADES};
+use crate::excel::scorecard::Scorecard;
+use crate::excel::types::{BacktestResult, CompanyData, ConfusionMatrix, GradeDefaultRate};
+use rust_xlsxwriter::{Format, Workbook};
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// 以秩和法计算 AUC,评分越低代表风险越高,同分记半
+fn auc(scored: &[(f64, bool)]) -> f64 {
+    let mut sorted = scored.to_vec();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut rank_sum = 0.0;
+    let mut i = 0;
+    while i < sorted.len() {
+        let mut j = i;
+        while j < sorted.len() && sorted[j].0 == sorted[i].0 {
+            j += 1;
+        }
+        // 同分样本取平均秩
+        let average_rank = (i + 1 + j) as f64 / 2.0;
+        rank_sum += average_rank * sorted[i..j].iter().filter(|(_, d)| !*d).count() as f64;
+        i = j;
+    }
+
+    let goods = sorted.iter().filter(|(_, d)| !*d).count() as f64;
+    let bads = sorted.len() as f64 - goods;
+    if goods == 0.0 || bads == 0.0 {
+        return 0.5;
+    }
+    (rank_sum - goods * (goods + 1.0) / 2.0) / (goods * bads)
+}
+
+/// 返回 KS 统计量及其对应的评分
+fn ks(scored: &[(f64, bool)]) -> (f64, f64) {
+    let mut sorted = scored.to_vec();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let bads = sorted.iter().filter(|(_, d)| *d).count();
+    let goods = sorted.len() - bads;
+
+    let (mut cum_bad, mut cum_good) = (0, 0);
+    let (mut best, mut best_score) = (0.0, 0.0);
+    let mut i = 0;
+    while i < sorted.len() {
+        let score = sorted[i].0;
+        while i < sorted.len() && sorted[i].0 == score {
+            if sorted[i].1 {
+                cum_bad += 1;
+            } else {
+                cum_good += 1;
+            }
+            i += 1;
+        }
+        let distance = (ratio(cum_bad, bads) - ratio(cum_good, goods)).abs();
+        if distance > best {
+            best = distance;
+            best_score = score;
+        }
+    }
+    (best, best_score)
+}
+
+fn confusion_matrix(scored: &[(f64, bool)], cutoff: f64) -> ConfusionMatrix {
+    let (mut tp, mut fp, mut tn, mut fn_) = (0, 0, 0, 0);
+    for (score, defaulted) in scored {
+        match (*score < cutoff, *defaulted) {
+            (true, true) => tp += 1,
+            (true, false) => fp += 1,
+            (false, false) => tn += 1,
+            (false, true) => fn_ += 1,
+        }
+    }
+    ConfusionMatrix {
+        cutoff,
+        true_positive: tp,
+        false_positive: fp,
+        true_negative: tn,
+        false_negative: fn_,
+        accuracy: ratio(tp + tn, scored.len()),
+        precision: ratio(tp, tp + fp),
+        recall: ratio(tp, tp + fn_),
+        false_positive_rate: ratio(fp, fp + tn),
+    }
+}
+
+/// 使用已知违约结果的样本回测评分卡的区分能力
+pub fn run_backtest(
+    file: &str,
+    samples: &[(CompanyData, bool)],
+    scorecard: &Scorecard,
+    cutoff: f64,
+) -> Result<BacktestResult, String> {
+    if samples.is_empty() {
+        return Err("回测样本为空".into());
+    }
+
+    let scored: Vec<(f64, bool)> = samples
+        .iter()
+        .map(|(c, d)| (calculate_credit_score(c, scorecard).credit_score, *d))
+        .collect();
+    let default_count = scored.iter().filter(|(_, d)| *d).count();
+
+    let auc = auc(&scored);
+    let (ks, ks_score) = ks(&scored);
+
+    // 评级由高到低排列
+    let grade_default_rates = RATING_GRADES
+        .iter()
+        .rev()
+        .map(|grade| {
+            let in_grade: Vec<bool> = scored
+                .iter()
+                .filter(|(s, _)| get_credit_rating(*s).0 == *grade)
+                .map(|(_, d)| *d)
+                .collect();
+            let defaults = in_grade.iter().filter(|d| **d).count();
+            GradeDefaultRate {
+                rating: grade.to_string(),
+                count: in_grade.len(),
+                defaults,
+                default_rate: ratio(defaults, in_grade.len()),
+            }
+        })
+        .collect();
+
+    Ok(BacktestResult {
+        file: file.to_string(),
+        scorecard: scorecard.id(),
+        sample_count: scored.len(),
+        default_count,
+        auc,
+        gini: 2.0 * auc - 1.0,
+        ks,
+        ks_score,
+        grade_default_rates,
+        confusion_matrix: confusion_matrix(&scored, cutoff),
+    })
+}
+
+/// 生成回测报告
+pub fn write_backtest_report(file_path: &str, result: &BacktestResult) -> Result<(), String> {
+    let mut workbook = Workbook::new();
+    let header_format = Format::new().set_bold().set_font_size(14.0);
+    let key_format = Format::new().set_bold();
+    let percent_format = Format::new().set_num_format("0.00%");
+    let number_format = Format::new().set_num_format("0.0000");
+
+    let summary = workbook.add_worksheet();
+    summary.set_name("回测概览").map_err(|e| e.to_string())?;
+    summary
+        .set_column_width(0, 20.0)
+        .map_err(|e| e.to_string())?;
+    summary
+        .set_column_width(1, 30.0)
+        .map_err(|e| e.to_string())?;
+
+    summary
+        .write_string_with_format(0, 0, "评分卡回测报告", &header_format)
+        .map_err(|e| e.to_string())?;
+
+    let cm = &result.confusion_matrix;
+    let texts = [
+        ("样本文件", result.file.clone()),
+        (
+            "评分卡",
+            format!("{} v{}", result.scorecard.name, result.scorecard.version),
+        ),
+        ("评分卡哈希", result.scorecard.hash.clone()),
+    ];
+    let numbers = [
+        ("样本数", result.sample_count as f64, None),
+        ("违约数", result.default_count as f64, None),
+        ("AUC", result.auc, Some(&number_format)),
+        ("Gini", result.gini, Some(&number_format)),
+        ("KS", result.ks, Some(&number_format)),
+        ("KS 对应评分", result.ks_score, None),
+        ("截断评分", cm.cutoff, None),
+        ("准确率", cm.accuracy, Some(&percent_format)),
+        ("精确率", cm.precision, Some(&percent_format)),
+        ("召回率", cm.recall, Some(&percent_format)),
+        ("误拒率", cm.false_positive_rate, Some(&percent_format)),
+    ];
+
+    let mut row = 2;
+    for (key, value) in &texts {
+        summary
+            .write_string_with_format(row, 0, *key, &key_format)
+            .map_err(|e| e.to_string())?;
+        summary
+            .write_string(row, 1, value)
+            .map_err(|e| e.to_string())?;
+        row += 1;
+    }
+    for (key, value, format) in &numbers {
+        summary
+            .write_string_with_format(row, 0, *key, &key_format)
+            .map_err(|e| e.to_string())?;
+        match format {
+            Some(format) => summary.write_number_with_format(row, 1, *value, format),
+            None => summary.write_number(row, 1, *value),
+        }
+        .map_err(|e| e.to_string())?;
+        row += 1;
+    }
+    row += 1;
+
+    summary
+        .write_string_with_format(row, 0, "混淆矩阵", &header_format)
+        .map_err(|e| e.to_string())?;
+    row += 1;
+    summary
+        .write_string_with_format(row, 1, "实际违约", &key_format)
+        .map_err(|e| e.to_string())?;
+    summary
+        .write_string_with_format(row, 2, "实际正常", &key_format)
+        .map_err(|e| e.to_string())?;
+    row += 1;
+    let matrix = [
+        ("预测违约", cm.true_positive, cm.false_positive),
+        ("预测正常", cm.false_negative, cm.true_negative),
+    ];
+    for (label, defaulted, normal) in matrix {
+        summary
+            .write_string_with_format(row, 0, label, &key_format)
+            .map_err(|e| e.to_string())?;
+        summary
+            .write_number(row, 1, defaulted as f64)
+            .map_err(|e| e.to_string())?;
+        summary
+            .write_number(row, 2, normal as f64)
+            .map_err(|e| e.to_string())?;
+        row += 1;
+    }
+
+    let grades = workbook.add_worksheet();
+    grades.set_name("分级违约率").map_err(|e| e.to_string())?;
+    for (col, header) in ["信用评级", "样本数", "违约数", "违约率"]
+        .iter()
+        .enumerate()
+    {
+        grades
+            .write_string_with_format(0, col as u16, *header, &key_format)
+            .map_err(|e| e.to_string())?;
+        grades
+            .set_column_width(col as u16, 15.0)
+            .map_err(|e| e.to_string())?;
+    }
+    for (i, grade) in result.grade_default_rates.iter().enumerate() {
+        let row = i as u32 + 1;
+        grades
+            .write_string(row, 0, &grade.rating)
+            .map_err(|e| e.to_string())?;
+        grades
+            .write_number(row, 1, grade.count as f64)
+            .map_err(|e| e.to_string())?;
+        grades
+            .write_number(row, 2, grade.defaults as f64)
+            .map_err(|e| e.to_string())?;
+        grades
+            .write_number_with_format(row, 3, grade.default_rate, &percent_format)
+            .map_err(|e| e.to_string())?;
+    }
+
+    workbook.save(file_path).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::excel::scorecard::DEFAULT_SCORECARD;
+
+    fn company(id: &str, net_profit: f64, debt_ratio: f64, overdue: u32) -> CompanyData {
+        serde_json::from_value(serde_json::json!({
+            "企业ID": id,
+            "企业名称": id,
+            "行业": "制造业",
+            "营业收入(万元)": 10000.0,
+            "净利润(万元)": net_profit,
+            "资产总额(万元)": 20000.0,
+            "负债总额(万元)": 200.0 * debt_ratio,
+            "资产负债率(%)": debt_ratio,
+            "研发投入占比(%)": 5.0,
+            "专利数量": 10,
+            "上游核心企业数量": 3,
+            "下游客户数量": 10,
+            "历史逾期次数": overdue,
+            "法律诉讼次数": overdue
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn auc_and_ks_measure_separation() {
+        let separated = [(10.0, true), (20.0, true), (30.0, false), (40.0, false)];
+        assert_eq!(auc(&separated), 1.0);
+        assert_eq!(ks(&separated), (1.0, 20.0));
+
+        // 四组好坏配对中有三组好样本评分更高
+        let mixed = [(10.0, true), (20.0, false), (30.0, true), (40.0, false)];
+        assert_eq!(auc(&mixed), 0.75);
+        assert_eq!(ks(&mixed), (0.5, 10.0));
+
+        // 同分记半,单一类别时视为无区分能力
+        assert_eq!(auc(&[(50.0, true), (50.0, false)]), 0.5);
+        assert_eq!(ks(&[(50.0, true), (50.0, false)]), (0.0, 0.0));
+        assert_eq!(auc(&[(10.0, false), (20.0, false)]), 0.5);
+    }
+
+    #[test]
+    fn confusion_matrix_flags_scores_below_cutoff() {
+        let scored = [
+            (10.0, true),
+            (20.0, false),
+            (30.0, false),
+            (40.0, true),
+            (50.0, false),
+        ];
+        let cm = confusion_matrix(&scored, 35.0);
+        assert_eq!(
+            (
+                cm.true_positive,
+                cm.false_positive,
+                cm.true_negative,
+                cm.false_negative
+            ),
+            (1, 2, 1, 1)
+        );
+        assert_eq!(cm.accuracy, 0.4);
+        assert!((cm.precision - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(cm.recall, 0.5);
+        assert!((cm.false_positive_rate - 2.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn backtest_scores_samples_with_the_scorecard() {
+        let scorecard = Scorecard::from_json(DEFAULT_SCORECARD).unwrap();
+        assert!(run_backtest("样本.xlsx", &[], &scorecard, 60.0).is_err());
+
+        let samples = [
+            (company("A", 2000.0, 25.0, 0), false),
+            (company("B", 1000.0, 45.0, 0), false),
+            (company("C", -500.0, 90.0, 5), true),
+        ];
+        let result = run_backtest("样本.xlsx", &samples, &scorecard, 60.0).unwrap();
+
+        assert_eq!((result.sample_count, result.default_count), (3, 1));
+        assert_eq!(result.auc, 1.0);
+        assert_eq!(result.gini, 1.0);
+        assert_eq!(result.ks, 1.0);
+        let counted: usize = result.grade_default_rates.iter().map(|g| g.count).sum();
+        let defaults: usize = result.grade_default_rates.iter().map(|g| g.defaults).sum();
+        assert_eq!((counted, defaults), (3, 1));
+        assert_eq!(result.confusion_matrix.true_positive, 1);
+        assert_eq!(result.confusion_matrix.false_positive, 0);
+    }
+}
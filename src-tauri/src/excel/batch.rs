@@ -0,0 +1,194 @@
+use crate::excel::types::{ImportProgress, ImportStage};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+use std::thread;
+
+/// 读取阶段每读取多少行回报一次进度
+const READ_REPORT_INTERVAL: usize = 1000;
+
+/// 当前导入任务的取消标记
+static CURRENT_IMPORT: LazyLock<Mutex<Option<Arc<AtomicBool>>>> =
+    LazyLock::new(|| Mutex::new(None));
+
+/// 取消正在进行的导入,没有进行中的导入时返回 false
+pub fn cancel_import() -> bool {
+    let current = CURRENT_IMPORT
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    match current.as_ref() {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 批量导入任务,在评分线程间共享进度计数与取消标记
+pub struct ImportTask {
+    cancelled: Arc<AtomicBool>,
+    scoring: AtomicBool,
+    files_total: usize,
+    files_done: AtomicUsize,
+    rows_read: AtomicUsize,
+    rows_scored: AtomicUsize,
+    rows_total: AtomicUsize,
+    /// 评分阶段各文件尚未评分的企业数
+    pending: Mutex<HashMap<String, usize>>,
+    on_progress: Box<dyn Fn(ImportProgress) + Send + Sync>,
+}
+
+impl ImportTask {
+    fn new(
+        files_total: usize,
+        cancelled: Arc<AtomicBool>,
+        on_progress: Box<dyn Fn(ImportProgress) + Send + Sync>,
+    ) -> Self {
+        ImportTask {
+            cancelled,
+            scoring: AtomicBool::new(false),
+            files_total,
+            files_done: AtomicUsize::new(0),
+            rows_read: AtomicUsize::new(0),
+            rows_scored: AtomicUsize::new(0),
+            rows_total: AtomicUsize::new(0),
+            pending: Mutex::new(HashMap::new()),
+            on_progress,
+        }
+    }
+
+    /// 登记为当前导入任务,可通过 cancel_import 取消;之前未结束的导入会被取消
+    pub fn start(
+        files_total: usize,
+        on_progress: impl Fn(ImportProgress) + Send + Sync + 'static,
+    ) -> Self {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let previous = CURRENT_IMPORT
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .replace(cancelled.clone());
+        if let Some(previous) = previous {
+            previous.store(true, Ordering::Relaxed);
+        }
+        Self::new(files_total, cancelled, Box::new(on_progress))
+    }
+
+    /// 不回报进度、不可取消的任务
+    pub fn silent() -> Self {
+        Self::new(0, Arc::default(), Box::new(|_| {}))
+    }
+
+    pub fn check_cancelled(&self) -> Result<(), String> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            Err("导入已取消".into())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn row_read(&self) {
+        let rows = self.rows_read.fetch_add(1, Ordering::Relaxed) + 1;
+        if rows.is_multiple_of(READ_REPORT_INTERVAL) {
+            self.report();
+        }
+    }
+
+    pub fn file_read(&self) {
+        self.files_done.fetch_add(1, Ordering::Relaxed);
+        self.report();
+    }
+
+    /// 进入评分阶段,rows_per_file 为各文件待评分的企业数
+    pub fn begin_scoring(&self, rows_per_file: HashMap<String, usize>) {
+        let rows_total = rows_per_file.values().sum();
+        // 没有可评分企业的文件直接计为完成
+        let files_done = self.files_total.saturating_sub(rows_per_file.len());
+        *self.pending.lock().unwrap_or_else(PoisonError::into_inner) = rows_per_file;
+        self.rows_total.store(rows_total, Ordering::Relaxed);
+        self.files_done.store(files_done, Ordering::Relaxed);
+        self.scoring.store(true, Ordering::Relaxed);
+        self.report();
+    }
+
+    /// 记录 file 中又有 rows 家企业完成评分
+    pub fn rows_scored(&self, file: &str, rows: usize) {
+        let file_finished = {
+            let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+            pending.get_mut(file).is_some_and(|left| {
+                *left = left.saturating_sub(rows);
+                *left == 0
+            })
+        };
+        if file_finished {
+            self.files_done.fetch_add(1, Ordering::Relaxed);
+        }
+        self.rows_scored.fetch_add(rows, Ordering::Relaxed);
+        self.report();
+    }
+
+    fn report(&self) {
+        let stage = if self.scoring.load(Ordering::Relaxed) {
+            ImportStage::Scoring
+        } else {
+            ImportStage::Reading
+        };
+        (self.on_progress)(ImportProgress {
+            stage,
+            files_done: self.files_done.load(Ordering::Relaxed),
+            files_total: self.files_total,
+            rows_read: self.rows_read.load(Ordering::Relaxed),
+            rows_scored: self.rows_scored.load(Ordering::Relaxed),
+            rows_total: self.rows_total.load(Ordering::Relaxed),
+        });
+    }
+}
+
+impl Drop for ImportTask {
+    fn drop(&mut self) {
+        let mut current = CURRENT_IMPORT
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if current
+            .as_ref()
+            .is_some_and(|c| Arc::ptr_eq(c, &self.cancelled))
+        {
+            *current = None;
+        }
+    }
+}
+
+/// 在多个线程上并行执行 f,结果保持输入顺序
+pub fn par_map<T: Send, R: Send>(items: Vec<T>, f: impl Fn(T) -> R + Sync) -> Vec<R> {
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(items.len());
+    if workers <= 1 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let next = queue.lock().unwrap_or_else(PoisonError::into_inner).next();
+                        let Some((i, item)) = next else {
+                            break;
+                        };
+                        done.push((i, f(item)));
+                    }
+                    done
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("评分线程异常退出"))
+            .collect()
+    });
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
@@ -1,108 +1,58 @@
-use crate::excel::types::{CompanyData, CompanyWithScore, ExcelResult, ScoreDetails};
-use calamine::{open_workbook_auto, Reader};
+use crate::excel::batch::{par_map, ImportTask};
+use crate::excel::cell::{CellValue, RowData};
+use crate::excel::consistency::{check_consistency, consistency_rules};
+use crate::excel::distress::assess_distress;
+use crate::excel::pd::predict_pd;
+use crate::excel::peer::benchmark_peers;
+use crate::excel::reader::{file_hash, read_companies};
+use crate::excel::scorecard::{active_scorecard, Evaluation, Scorecard};
+use crate::excel::types::{CompanyData, CompanyWithScore, Diagnostic, ExcelResult, Provenance};
 use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-pub fn calculate_credit_score(company: &CompanyData) -> (f64, ScoreDetails) {
-    let mut raw_details = ScoreDetails {
-        financial_score: 0.0,
-        innovation_score: 0.0,
-        supply_chain_score: 0.0,
-        risk_score: 0.0,
-        industry_adjustment: 0.0,
-    };
-
-    // 财务评分
-    let profit_margin = if company.revenue > 0.0 {
-        company.net_profit / company.revenue
-    } else {
-        0.0
-    };
-    raw_details.financial_score += match profit_margin {
-        x if x >= 0.15 => 15.0,
-        x if x >= 0.10 => 12.0,
-        x if x >= 0.05 => 8.0,
-        x if x >= 0.0 => 5.0,
-        _ => 0.0,
-    };
-    raw_details.financial_score += match company.debt_to_asset_ratio {
-        x if x <= 30.0 => 15.0,
-        x if x <= 50.0 => 12.0,
-        x if x <= 70.0 => 8.0,
-        x if x <= 85.0 => 4.0,
-        _ => 0.0,
-    };
-    raw_details.financial_score += match company.total_assets {
-        x if x >= 50000.0 => 10.0,
-        x if x >= 20000.0 => 8.0,
-        x if x >= 10000.0 => 6.0,
-        x if x >= 5000.0 => 4.0,
-        _ => 2.0,
-    };
-
-    // 创新评分
-    raw_details.innovation_score += match company.r_and_d_ratio {
-        x if x >= 15.0 => 15.0,
-        x if x >= 10.0 => 12.0,
-        x if x >= 5.0 => 8.0,
-        x if x >= 3.0 => 5.0,
-        _ => 2.0,
-    };
-    raw_details.innovation_score += match company.patent_count {
-        x if x >= 50 => 15.0,
-        x if x >= 20 => 12.0,
-        x if x >= 10 => 9.0,
-        x if x >= 5 => 6.0,
-        x if x >= 1 => 3.0,
-        _ => 0.0,
-    };
-
-    // 供应链评分
-    raw_details.supply_chain_score += match company.upstream_core_companies {
-        x if x >= 5 => 10.0,
-        x if x >= 3 => 8.0,
-        x if x >= 2 => 6.0,
-        x if x >= 1 => 4.0,
-        _ => 0.0,
-    };
-    raw_details.supply_chain_score += match company.downstream_customers {
-        x if x >= 20 => 10.0,
-        x if x >= 10 => 8.0,
-        x if x >= 5 => 6.0,
-        x if x >= 3 => 4.0,
-        _ => 2.0,
-    };
-
-    // 风险评分
-    let mut risk_score = 10.0;
-    risk_score -= 2.0 * (company.overdue_count as f64).min(5.0).sqrt();
-    risk_score -= 3.0 * (company.legal_disputes_count as f64).min(5.0).sqrt();
-    raw_details.risk_score = risk_score.max(2.0);
+/// 按评分卡计算信用评分
+pub fn calculate_credit_score(company: &CompanyData, scorecard: &Scorecard) -> Evaluation {
+    scorecard.evaluate(company)
+}
 
-    // 行业调整分
-    raw_details.industry_adjustment = match company.industry.as_str() {
-        "人工智能" | "新能源" | "生物医药" | "新材料" => 5.0,
-        "高端制造" | "信息技术" | "节能环保" => 3.0,
-        "传统制造" | "房地产" => -3.0,
-        "高污染" | "高能耗" => -5.0,
-        _ => 0.0,
-    };
+/// 按指定评分卡对单个企业评分并生成评级结果
+pub fn score_company(company: CompanyData, scorecard: &Scorecard) -> CompanyWithScore {
+    let evaluation = calculate_credit_score(&company, scorecard);
+    let (credit_rating, credit_limit, risk_level) = get_credit_rating(evaluation.credit_score);
+    let probability_of_default = predict_pd(&company);
+    let distress = assess_distress(&company);
+
+    CompanyWithScore {
+        company_data: company,
+        credit_score: evaluation.credit_score,
+        credit_rating,
+        credit_limit,
+        risk_level,
+        score_details: evaluation.score_details,
+        factor_details: evaluation.factor_details,
+        reason_codes: evaluation.reason_codes,
+        scorecard: scorecard.id(),
+        probability_of_default,
+        distress,
+        warnings: Vec::new(),
+        provenance: None,
+    }
+}
 
-    let total_score = raw_details.financial_score
-        + raw_details.innovation_score
-        + raw_details.supply_chain_score
-        + raw_details.risk_score
-        + raw_details.industry_adjustment;
+/// 信用评级由低到高排列
+pub const RATING_GRADES: [&str; 9] = ["C", "CC", "CCC", "B", "BB", "BBB", "A", "AA", "AAA"];
 
-    // 归一化百分制显示
-    let normalized_details = ScoreDetails {
-        financial_score: (raw_details.financial_score / 40.0) * 100.0,
-        innovation_score: (raw_details.innovation_score / 30.0) * 100.0,
-        supply_chain_score: (raw_details.supply_chain_score / 20.0) * 100.0,
-        risk_score: (raw_details.risk_score / 10.0) * 100.0,
-        industry_adjustment: raw_details.industry_adjustment,
-    };
+/// 评级在 RATING_GRADES 中的位置,数值越大评级越高
+pub fn rating_rank(rating: &str) -> Option<usize> {
+    RATING_GRADES.iter().position(|g| *g == rating)
+}
 
-    (total_score.max(0.0).min(100.0), normalized_details)
+/// 评级变动档数,正数为上调;评级不在评级表中时视为未变动
+pub fn rating_notches(from: &str, to: &str) -> i32 {
+    match (rating_rank(from), rating_rank(to)) {
+        (Some(from), Some(to)) => to as i32 - from as i32,
+        _ => 0,
+    }
 }
 
 pub fn get_credit_rating(score: f64) -> (String, String, String) {
@@ -120,216 +70,189 @@ pub fn get_credit_rating(score: f64) -> (String, String, String) {
 }
 
 pub fn extract_companies_from_excel(paths: Vec<String>) -> Result<Vec<CompanyData>, String> {
-    let mut all_companies = Vec::new();
-
-    for path in paths {
-        let mut workbook =
-            open_workbook_auto(&path).map_err(|e| format!("无法打开文件 {}: {}", path, e))?;
-
-        for sheet_name in workbook.sheet_names().to_owned() {
-            let range = workbook
-                .worksheet_range(&sheet_name)
-                .map_err(|e| format!("读取工作表 {} 失败: {}", sheet_name, e))?;
-
-            if range.is_empty() {
-                continue;
-            }
-
-            let mut rows = range.rows();
-            let headers: Vec<String> = match rows.next() {
-                Some(header_row) => header_row.iter().map(|c| c.to_string()).collect(),
-                None => continue,
-            };
-
-            let header_map: HashMap<String, usize> = headers
-                .iter()
-                .enumerate()
-                .map(|(i, h)| (h.trim().to_string(), i))
-                .collect();
-
-            for row in rows {
-                let mut row_data = HashMap::new();
-                for (col_name, &col_idx) in &header_map {
-                    if col_idx < row.len() {
-                        row_data.insert(col_name.clone(), row[col_idx].to_string());
-                    }
-                }
-
-                let company = parse_company_from_row(&row_data);
-
-                if !company.company_id.is_empty() || !company.company_name.is_empty() {
-                    all_companies.push(company);
-                }
-            }
-        }
-    }
-
-    Ok(all_companies)
+    let (entries, _) = read_companies(&paths, None, &ImportTask::silent())?;
+    Ok(entries.into_iter().map(|(_, company)| company).collect())
 }
 
-fn parse_company_from_row(row_data: &HashMap<String, String>) -> CompanyData {
+pub fn parse_company_from_row(row_data: &RowData) -> CompanyData {
+    let text = |name: &str| {
+        row_data
+            .get(name)
+            .map(CellValue::as_text)
+            .unwrap_or_default()
+    };
+    let number = |name: &str| row_data.get(name).and_then(CellValue::as_number);
+    let integer = |name: &str| {
+        row_data
+            .get(name)
+            .and_then(CellValue::as_integer)
+            .unwrap_or(0)
+    };
+
     CompanyData {
-        company_id: row_data.get("企业ID").cloned().unwrap_or_default(),
-        company_name: row_data.get("企业名称").cloned().unwrap_or_default(),
-        industry: row_data.get("行业").cloned().unwrap_or_default(),
-        revenue: row_data
-            .get("营业收入(万元)")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0.0),
-        net_profit: row_data
-            .get("净利润(万元)")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0.0),
-        total_assets: row_data
-            .get("资产总额(万元)")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0.0),
-        total_liabilities: row_data
-            .get("负债总额(万元)")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0.0),
-        debt_to_asset_ratio: row_data
-            .get("资产负债率(%)")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0.0),
-        r_and_d_ratio: row_data
-            .get("研发投入占比(%)")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0.0),
-        patent_count: row_data
-            .get("专利数量")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0),
-        upstream_core_companies: row_data
-            .get("上游核心企业数量")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0),
-        downstream_customers: row_data
-            .get("下游客户数量")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0),
-        overdue_count: row_data
-            .get("历史逾期次数")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0),
-        legal_disputes_count: row_data
-            .get("法律诉讼次数")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0),
+        company_id: text("企业ID"),
+        company_name: text("企业名称"),
+        industry: text("行业"),
+        revenue: number("营业收入(万元)").unwrap_or(0.0),
+        net_profit: number("净利润(万元)").unwrap_or(0.0),
+        total_assets: number("资产总额(万元)").unwrap_or(0.0),
+        total_liabilities: number("负债总额(万元)").unwrap_or(0.0),
+        debt_to_asset_ratio: number("资产负债率(%)").unwrap_or(0.0),
+        r_and_d_ratio: number("研发投入占比(%)").unwrap_or(0.0),
+        patent_count: integer("专利数量"),
+        upstream_core_companies: integer("上游核心企业数量"),
+        downstream_customers: integer("下游客户数量"),
+        overdue_count: integer("历史逾期次数"),
+        legal_disputes_count: integer("法律诉讼次数"),
+        working_capital: number("营运资金(万元)"),
+        retained_earnings: number("留存收益(万元)"),
+        ebit: number("息税前利润(万元)"),
+        current_assets: number("流动资产(万元)"),
+        current_liabilities: number("流动负债(万元)"),
+        operating_cash_flow: number("经营活动现金流量(万元)"),
+        fiscal_year: ["年度", "会计年度", "财务年度"]
+            .iter()
+            .find_map(|h| row_data.get(*h))
+            .and_then(CellValue::as_year),
+        history: Vec::new(),
+        original_units: Default::default(),
     }
 }
 
-pub fn process_excel_internal(paths: Vec<String>) -> Result<Vec<ExcelResult>, String> {
-    let mut all_results = Vec::new();
-    let mut path_to_companies: HashMap<String, HashMap<String, Vec<CompanyData>>> = HashMap::new();
-
+/// 每个评分任务包含的企业数
+const SCORE_CHUNK: usize = 1000;
+
+/// 读取并评分,各工作表的企业分块后在多个线程上并行评分,进度与取消通过 task 传递
+pub fn process_excel_batch(
+    paths: Vec<String>,
+    source: Option<&str>,
+    task: &ImportTask,
+) -> Result<(Vec<ExcelResult>, Vec<Diagnostic>), String> {
+    let imported_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    let mut hashes = HashMap::new();
     for path in &paths {
-        let mut workbook =
-            open_workbook_auto(path).map_err(|e| format!("无法打开文件 {}: {}", path, e))?;
-
-        for sheet_name in workbook.sheet_names().to_owned() {
-            let range = workbook
-                .worksheet_range(&sheet_name)
-                .map_err(|e| format!("读取工作表 {} 失败: {}", sheet_name, e))?;
-
-            if range.is_empty() {
-                continue;
-            }
-
-            let mut rows = range.rows();
-            let headers: Vec<String> = match rows.next() {
-                Some(header_row) => header_row.iter().map(|c| c.to_string()).collect(),
-                None => continue,
-            };
-
-            let header_map: HashMap<String, usize> = headers
-                .iter()
-                .enumerate()
-                .map(|(i, h)| (h.trim().to_string(), i))
-                .collect();
-
-            let mut companies = Vec::new();
-
-            for row in rows {
-                let mut row_data = HashMap::new();
-                for (col_name, &col_idx) in &header_map {
-                    if col_idx < row.len() {
-                        row_data.insert(col_name.clone(), row[col_idx].to_string());
-                    }
-                }
-
-                let company = parse_company_from_row(&row_data);
-
-                if company.company_id.is_empty() && company.company_name.is_empty() {
-                    continue;
-                }
-
-                companies.push(company);
-            }
+        hashes.insert(path.clone(), file_hash(path)?);
+    }
+    let (entries, diagnostics) = read_companies(&paths, source, task)?;
+
+    // 整批使用同一评分卡,避免处理过程中切换规则导致结果不一致
+    let scorecard = active_scorecard();
+    let rules = consistency_rules();
+
+    // 按文件、工作表首次出现的顺序分组,组内保持原始行序
+    let mut all_results: Vec<ExcelResult> = Vec::new();
+    let mut sheets: Vec<Vec<(usize, CompanyData)>> = Vec::new();
+    let mut sheet_index: HashMap<(String, String), usize> = HashMap::new();
+    for (location, company) in entries {
+        let key = (location.file, location.sheet_name);
+        let index = *sheet_index.entry(key.clone()).or_insert_with(|| {
+            all_results.push(ExcelResult {
+                file: key.0,
+                sheet_name: key.1,
+                total_companies: 0,
+                scorecard: scorecard.id(),
+                companies: Vec::new(),
+                peer_stats: Vec::new(),
+            });
+            sheets.push(Vec::new());
+            all_results.len() - 1
+        });
+        sheets[index].push((location.row, company));
+    }
 
-            if !companies.is_empty() {
-                path_to_companies
-                    .entry(path.clone())
-                    .or_insert_with(HashMap::new)
-                    .insert(sheet_name, companies);
+    let mut rows_per_file: HashMap<String, usize> = HashMap::new();
+    for (result, companies) in all_results.iter().zip(&sheets) {
+        *rows_per_file.entry(result.file.clone()).or_default() += companies.len();
+    }
+    task.begin_scoring(rows_per_file);
+
+    let mut chunks = Vec::new();
+    for (index, companies) in sheets.into_iter().enumerate() {
+        let mut companies = companies.into_iter();
+        loop {
+            let chunk: Vec<(usize, CompanyData)> = companies.by_ref().take(SCORE_CHUNK).collect();
+            if chunk.is_empty() {
+                break;
             }
+            chunks.push((index, chunk));
         }
     }
 
-    // 处理每个文件的每个 sheet
-    for (path, sheets) in path_to_companies {
-        for (sheet_name, companies) in sheets {
-            let companies_with_score: Vec<CompanyWithScore> = companies
-                .into_iter()
-                .map(|company| {
-                    let (credit_score, score_details) = calculate_credit_score(&company);
-                    let (credit_rating, credit_limit, risk_level) = get_credit_rating(credit_score);
+    let scored = par_map(chunks, |(index, chunk)| {
+        task.check_cancelled()?;
+        let rows = chunk.len();
+        let sheet = &all_results[index];
+        let companies: Vec<CompanyWithScore> = chunk
+            .into_iter()
+            .map(|(row, mut company)| {
+                let warnings = check_consistency(&mut company, &rules);
+                let mut scored = score_company(company, &scorecard);
+                scored.warnings = warnings;
+                scored.provenance = Some(Provenance {
+                    file: sheet.file.clone(),
+                    file_hash: hashes[&sheet.file].clone(),
+                    sheet_name: sheet.sheet_name.clone(),
+                    row,
+                    imported_at,
+                });
+                scored
+            })
+            .collect();
+        task.rows_scored(&sheet.file, rows);
+        Ok::<_, String>((index, companies))
+    });
+    for chunk in scored {
+        let (index, companies) = chunk?;
+        all_results[index].companies.extend(companies);
+    }
+    for result in &mut all_results {
+        result.total_companies = result.companies.len();
+    }
+
+    benchmark_peers(&mut all_results);
 
-                    CompanyWithScore {
-                        company_data: company,
-                        credit_score,
-                        credit_rating,
-                        credit_limit,
-                        risk_level,
-                        score_details,
-                    }
-                })
-                .collect();
+    Ok((all_results, diagnostics))
+}
 
-            all_results.push(ExcelResult {
-                file: path.clone(),
-                sheet_name,
-                total_companies: companies_with_score.len(),
-                companies: companies_with_score,
-            });
-        }
+/// 建议授信敞口(万元),逐一对应 get_credit_rating 的额度档位,区间取中值;
+/// 需要担保的企业不给予信用敞口,与拒绝授信及无法识别的额度一样为 0
+pub fn suggested_exposure(credit_limit: &str) -> f64 {
+    match credit_limit {
+        "1000万以上" => 1000.0,
+        "800-1000万" => 900.0,
+        "500-800万" => 650.0,
+        "300-500万" => 400.0,
+        "100-300万" => 200.0,
+        "50-100万" => 75.0,
+        "50万以下" => 50.0,
+        _ => 0.0,
     }
-
-    Ok(all_results)
 }
 
-pub fn parse_credit_limit(limit_str: &str) -> f64 {
-    if limit_str.contains("以上") {
-        limit_str
-            .replace("万以上", "")
-            .parse::<f64>()
-            .unwrap_or(1000.0)
-    } else if limit_str.contains("以下") {
-        limit_str
-            .replace("万以下", "")
-            .parse::<f64>()
-            .unwrap_or(50.0)
-    } else if limit_str.contains('-') {
-        let cleaned = limit_str.replace('万', "");
-        let parts: Vec<&str> = cleaned.split('-').collect();
-        if parts.len() == 2 {
-            let low = parts[0].parse::<f64>().unwrap_or(0.0);
-            let high = parts[1].parse::<f64>().unwrap_or(low);
-            (low + high) / 2.0
-        } else {
-            100.0
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggested_exposure_does_not_increase_as_rating_worsens() {
+        let mut previous = f64::INFINITY;
+        for score in (0..=100).rev() {
+            let (rating, limit, _) = get_credit_rating(score as f64);
+            let exposure = suggested_exposure(&limit);
+            assert!(
+                exposure <= previous,
+                "{} ({}) 的敞口 {} 高于更好评级的 {}",
+                rating,
+                limit,
+                exposure,
+                previous
+            );
+            previous = exposure;
         }
-    } else {
-        100.0
+        assert_eq!(suggested_exposure("需要担保"), 0.0);
+        assert_eq!(suggested_exposure("拒绝授信"), 0.0);
     }
 }
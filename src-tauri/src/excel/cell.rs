@@ -0,0 +1,150 @@
+use calamine::Data;
+use std::collections::HashMap;
+
+/// 百分比列,以百分点计,如 35 表示 35%
+pub const PERCENT_COLUMNS: [&str; 2] = ["资产负债率(%)", "研发投入占比(%)"];
+/// 百分比列名末尾括号中表示按小数填写的单位(规范化后),如 "资产负债率(小数)"
+const FRACTION_UNITS: [&str; 4] = ["小数", "比例", "ratio", "fraction"];
+
+/// 导入的单元格取值,保留原始类型
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    Empty,
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    /// 日期单元格 (年, 月, 日)
+    Date(u16, u8, u8),
+    /// 公式错误,如 #DIV/0!、#N/A
+    Error(String),
+}
+
+pub type RowData = HashMap<String, CellValue>;
+
+impl From<&Data> for CellValue {
+    fn from(data: &Data) -> Self {
+        match data {
+            Data::Empty => CellValue::Empty,
+            Data::String(s) => CellValue::Text(s.clone()),
+            Data::Int(i) => CellValue::Number(*i as f64),
+            Data::Float(f) => CellValue::Number(*f),
+            Data::Bool(b) => CellValue::Bool(*b),
+            Data::DateTime(dt) if dt.is_datetime() => {
+                let (y, m, d, ..) = dt.to_ymd_hms_milli();
+                CellValue::Date(y, m, d)
+            }
+            Data::DateTime(dt) => CellValue::Number(dt.as_f64()),
+            Data::DateTimeIso(s) => parse_iso_date(s).unwrap_or_else(|| CellValue::Text(s.clone())),
+            Data::DurationIso(s) => CellValue::Text(s.clone()),
+            Data::Error(e) => CellValue::Error(e.to_string()),
+        }
+    }
+}
+
+fn parse_iso_date(s: &str) -> Option<CellValue> {
+    let mut parts = s.get(..10)?.split('-');
+    let y = parts.next()?.parse().ok()?;
+    let m = parts.next()?.parse().ok()?;
+    let d = parts.next()?.parse().ok()?;
+    Some(CellValue::Date(y, m, d))
+}
+
+impl CellValue {
+    pub fn is_blank(&self) -> bool {
+        match self {
+            CellValue::Empty => true,
+            CellValue::Text(s) => s.trim().is_empty(),
+            _ => false,
+        }
+    }
+
+    /// 单元格的文本形式,日期为 YYYY-MM-DD
+    pub fn as_text(&self) -> String {
+        match self {
+            CellValue::Empty => String::new(),
+            CellValue::Text(s) => s.trim().to_string(),
+            CellValue::Number(x) => x.to_string(),
+            CellValue::Bool(b) => b.to_string(),
+            CellValue::Date(y, m, d) => format!("{:04}-{:02}-{:02}", y, m, d),
+            CellValue::Error(e) => e.clone(),
+        }
+    }
+
+    /// 解析为数值,文本末尾的 % 视为百分点,如 "35%" 为 35
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            CellValue::Number(x) => Some(*x),
+            CellValue::Text(s) => {
+                let s = s.trim();
+                s.strip_suffix('%').unwrap_or(s).trim().parse().ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// 解析为整数,允许 12.0 这类以小数存储的整数
+    pub fn as_integer(&self) -> Option<i32> {
+        let x = self.as_number()?;
+        (x.fract() == 0.0 && x >= i32::MIN as f64 && x <= i32::MAX as f64).then_some(x as i32)
+    }
+
+    /// 解析年度,支持 2023、"2023年" 及日期单元格
+    pub fn as_year(&self) -> Option<i32> {
+        match self {
+            CellValue::Date(y, ..) => Some(i32::from(*y)),
+            CellValue::Text(s) => s.trim().trim_end_matches('年').parse().ok(),
+            _ => self.as_integer(),
+        }
+    }
+}
+
+pub fn is_percent(column: &str) -> bool {
+    PERCENT_COLUMNS.contains(&column)
+}
+
+/// 从规范化列名末尾的括号中识别小数单位,返回去掉单位后的列名
+pub fn split_fraction_unit(normalized: &str) -> Option<&str> {
+    let body = normalized.strip_suffix(')')?;
+    let open = body.rfind('(')?;
+    FRACTION_UNITS
+        .contains(&&body[open + 1..])
+        .then(|| &body[..open])
+}
+
+/// 百分比列中按小数填写的取值,如 0.35;带 % 的文本本身即为百分点
+fn fraction(value: &CellValue) -> Option<f64> {
+    match value {
+        CellValue::Number(x) => Some(*x),
+        CellValue::Text(s) if !s.trim().ends_with('%') => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// 将按小数填写的百分比取值换算为百分点,返回是否换算
+pub fn rescale_fraction(value: &mut CellValue) -> bool {
+    let Some(x) = fraction(value) else {
+        return false;
+    };
+    // 保留 6 位小数,避免 0.07 * 100 产生的浮点误差
+    *value = CellValue::Number((x * 1e8).round() / 1e6);
+    true
+}
+
+/// 未换算的百分比取值落在 (0, 1] 内时可能是按小数填写的
+pub fn looks_like_fraction(value: &CellValue) -> bool {
+    fraction(value).is_some_and(|x| x > 0.0 && x <= 1.0)
+}
+
+/// 按表头将一行单元格转换为 列名 -> 取值,同名列以第一列为准
+pub fn row_from_cells(headers: &[String], cells: impl IntoIterator<Item = CellValue>) -> RowData {
+    let mut data = RowData::new();
+    for (header, value) in headers.iter().zip(cells) {
+        data.entry(header.clone()).or_insert(value);
+    }
+    data
+}
+
+/// 转换 Excel 行
+pub fn row_data(headers: &[String], row: &[Data]) -> RowData {
+    row_from_cells(headers, row.iter().map(CellValue::from))
+}
@@ -0,0 +1,351 @@
+use crate::excel::history::sync_latest_period;
+use crate::excel::types::{CompanyData, ConsistencyWarning};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{LazyLock, PoisonError, RwLock};
+
+/// 内置一致性规则
+const DEFAULT_RULES: &str = include_str!("../../rules/consistency.json");
+
+static RULES: LazyLock<RwLock<RuleSet>> = LazyLock::new(|| {
+    RwLock::new(RuleSet {
+        path: None,
+        rules: serde_json::from_str(DEFAULT_RULES).expect("内置一致性规则格式错误"),
+    })
+});
+
+struct RuleSet {
+    path: Option<PathBuf>,
+    rules: Vec<ConsistencyRule>,
+}
+
+/// 参与一致性校验的数值字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumericField {
+    Revenue,
+    NetProfit,
+    TotalAssets,
+    TotalLiabilities,
+    DebtToAssetRatio,
+    RAndDRatio,
+    WorkingCapital,
+    RetainedEarnings,
+    Ebit,
+    CurrentAssets,
+    CurrentLiabilities,
+    OperatingCashFlow,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// 仅提示
+    #[default]
+    Flag,
+    /// 以推算值覆盖原值
+    Correct,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Check {
+    /// target ≈ numerator / denominator * scale
+    Ratio {
+        target: NumericField,
+        numerator: NumericField,
+        denominator: NumericField,
+        #[serde(default = "default_scale")]
+        scale: f64,
+        tolerance: f64,
+    },
+    /// target ≈ minuend - subtrahend
+    Difference {
+        target: NumericField,
+        minuend: NumericField,
+        subtrahend: NumericField,
+        tolerance: f64,
+    },
+    /// |field| ≤ max_multiple * |reference|
+    Bound {
+        field: NumericField,
+        reference: NumericField,
+        max_multiple: f64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyRule {
+    pub key: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub check: Check,
+    #[serde(default)]
+    pub action: Action,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+impl NumericField {
+    pub fn get(&self, c: &CompanyData) -> Option<f64> {
+        match self {
+            NumericField::Revenue => Some(c.revenue),
+            NumericField::NetProfit => Some(c.net_profit),
+            NumericField::TotalAssets => Some(c.total_assets),
+            NumericField::TotalLiabilities => Some(c.total_liabilities),
+            NumericField::DebtToAssetRatio => Some(c.debt_to_asset_ratio),
+            NumericField::RAndDRatio => Some(c.r_and_d_ratio),
+            NumericField::WorkingCapital => c.working_capital,
+            NumericField::RetainedEarnings => c.retained_earnings,
+            NumericField::Ebit => c.ebit,
+            NumericField::CurrentAssets => c.current_assets,
+            NumericField::CurrentLiabilities => c.current_liabilities,
+            NumericField::OperatingCashFlow => c.operating_cash_flow,
+        }
+    }
+
+    pub fn set(&self, c: &mut CompanyData, value: f64) {
+        match self {
+            NumericField::Revenue => c.revenue = value,
+            NumericField::NetProfit => c.net_profit = value,
+            NumericField::TotalAssets => c.total_assets = value,
+            NumericField::TotalLiabilities => c.total_liabilities = value,
+            NumericField::DebtToAssetRatio => c.debt_to_asset_ratio = value,
+            NumericField::RAndDRatio => c.r_and_d_ratio = value,
+            NumericField::WorkingCapital => c.working_capital = Some(value),
+            NumericField::RetainedEarnings => c.retained_earnings = Some(value),
+            NumericField::Ebit => c.ebit = Some(value),
+            NumericField::CurrentAssets => c.current_assets = Some(value),
+            NumericField::CurrentLiabilities => c.current_liabilities = Some(value),
+            NumericField::OperatingCashFlow => c.operating_cash_flow = Some(value),
+        }
+    }
+
+    fn key(&self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .unwrap_or_default()
+    }
+}
+
+impl ConsistencyRule {
+    fn validate(&self) -> Result<(), String> {
+        let fail = |msg: &str| Err(format!("一致性规则 {} 无效: {}", self.key, msg));
+        match &self.check {
+            Check::Ratio {
+                scale, tolerance, ..
+            } => {
+                if !scale.is_finite() || *scale == 0.0 {
+                    return fail("scale 不能为 0");
+                }
+                if !tolerance.is_finite() || *tolerance < 0.0 {
+                    return fail("tolerance 不能为负数");
+                }
+            }
+            Check::Difference { tolerance, .. } => {
+                if !tolerance.is_finite() || *tolerance < 0.0 {
+                    return fail("tolerance 不能为负数");
+                }
+            }
+            Check::Bound { max_multiple, .. } => {
+                if !max_multiple.is_finite() || *max_multiple < 0.0 {
+                    return fail("max_multiple 不能为负数");
+                }
+                if self.action == Action::Correct {
+                    return fail("上限规则只能提示,不能自动修正");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查单条规则,必要时修正企业数据
+    fn apply(&self, company: &mut CompanyData) -> Option<ConsistencyWarning> {
+        let (target, expected, tolerance) = match &self.check {
+            Check::Ratio {
+                target,
+                numerator,
+                denominator,
+                scale,
+                tolerance,
+            } => {
+                let denominator = denominator.get(company).filter(|d| *d != 0.0)?;
+                (
+                    target,
+                    numerator.get(company)? / denominator * scale,
+                    *tolerance,
+                )
+            }
+            Check::Difference {
+                target,
+                minuend,
+                subtrahend,
+                tolerance,
+            } => (
+                target,
+                minuend.get(company)? - subtrahend.get(company)?,
+                *tolerance,
+            ),
+            Check::Bound {
+                field,
+                reference,
+                max_multiple,
+            } => {
+                let value = field.get(company)?;
+                let limit = reference.get(company)?.abs() * max_multiple;
+                if value.abs() <= limit {
+                    return None;
+                }
+                return Some(ConsistencyWarning {
+                    rule: self.key.clone(),
+                    field: field.key(),
+                    message: format!("{}: 当前值 {} 超过上限 {}", self.name, value, limit),
+                    original: value,
+                    expected: limit,
+                    corrected: false,
+                });
+            }
+        };
+
+        let original = target.get(company)?;
+        if (original - expected).abs() <= tolerance {
+            return None;
+        }
+        let corrected = self.action == Action::Correct;
+        if corrected {
+            target.set(company, expected);
+        }
+        let expected = (expected * 100.0).round() / 100.0;
+        Some(ConsistencyWarning {
+            rule: self.key.clone(),
+            field: target.key(),
+            message: if corrected {
+                format!(
+                    "{}: 填报值 {} 与推算值 {} 不符,已修正",
+                    self.name, original, expected
+                )
+            } else {
+                format!(
+                    "{}: 填报值 {} 与推算值 {} 不符",
+                    self.name, original, expected
+                )
+            },
+            original,
+            expected,
+            corrected,
+        })
+    }
+}
+
+/// 启动时加载用户保存的一致性规则
+pub fn init_consistency_rules(path: PathBuf) -> Result<(), String> {
+    let mut set = RULES.write().unwrap_or_else(PoisonError::into_inner);
+    if let Ok(content) = fs::read_to_string(&path) {
+        match parse_rules(&content) {
+            Ok(rules) => set.rules = rules,
+            Err(e) => eprintln!("加载一致性规则失败: {}", e),
+        }
+    }
+    set.path = Some(path);
+    Ok(())
+}
+
+fn parse_rules(content: &str) -> Result<Vec<ConsistencyRule>, String> {
+    let rules: Vec<ConsistencyRule> =
+        serde_json::from_str(content).map_err(|e| format!("一致性规则格式错误: {}", e))?;
+    for rule in &rules {
+        rule.validate()?;
+    }
+    Ok(rules)
+}
+
+pub fn consistency_rules() -> Vec<ConsistencyRule> {
+    RULES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .rules
+        .clone()
+}
+
+/// 替换并保存一致性规则
+pub fn save_consistency_rules(rules: Vec<ConsistencyRule>) -> Result<(), String> {
+    for rule in &rules {
+        rule.validate()?;
+    }
+    let mut set = RULES.write().unwrap_or_else(PoisonError::into_inner);
+    if let Some(path) = &set.path {
+        let content = serde_json::to_string_pretty(&rules).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| format!("保存一致性规则失败: {}", e))?;
+    }
+    set.rules = rules;
+    Ok(())
+}
+
+/// 按当前规则检查企业数据,返回提示信息;自动修正的规则会直接更新数据,
+/// 同一年度的最新一期历史数据随之更新
+pub fn check_consistency(
+    company: &mut CompanyData,
+    rules: &[ConsistencyRule],
+) -> Vec<ConsistencyWarning> {
+    let warnings = rules.iter().filter_map(|r| r.apply(company)).collect();
+    sync_latest_period(company);
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::excel::history::debt_ratio_change;
+    use crate::excel::types::FinancialPeriod;
+
+    fn period(
+        fiscal_year: i32,
+        total_liabilities: f64,
+        debt_to_asset_ratio: f64,
+    ) -> FinancialPeriod {
+        FinancialPeriod {
+            fiscal_year,
+            revenue: 1000.0,
+            net_profit: 100.0,
+            total_assets: 2000.0,
+            total_liabilities,
+            debt_to_asset_ratio,
+            r_and_d_ratio: 5.0,
+        }
+    }
+
+    #[test]
+    fn corrected_ratio_reaches_latest_period() {
+        let mut company: CompanyData = serde_json::from_value(serde_json::json!({
+            "企业ID": "C1",
+            "企业名称": "甲",
+            "行业": "制造业",
+            "营业收入(万元)": 1000.0,
+            "净利润(万元)": 100.0,
+            "资产总额(万元)": 2000.0,
+            "负债总额(万元)": 1000.0,
+            "资产负债率(%)": 5.0,
+            "研发投入占比(%)": 5.0,
+            "专利数量": 3,
+            "上游核心企业数量": 2,
+            "下游客户数量": 5,
+            "历史逾期次数": 0,
+            "法律诉讼次数": 0,
+            "年度": 2023
+        }))
+        .unwrap();
+        company.history = vec![period(2022, 800.0, 40.0), period(2023, 1000.0, 5.0)];
+
+        let rules: Vec<ConsistencyRule> = serde_json::from_str(DEFAULT_RULES).unwrap();
+        let warnings = check_consistency(&mut company, &rules);
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(company.debt_to_asset_ratio, 50.0);
+        assert_eq!(company.history[1].debt_to_asset_ratio, 50.0);
+        assert_eq!(debt_ratio_change(&company), Some(10.0));
+    }
+}
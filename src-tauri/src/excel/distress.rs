@@ -0,0 +1,191 @@
+use crate::excel::types::{CompanyData, DistressIndicators};
+
+/// Altman Z'' 区间:高于 2.6 为安全区,低于 1.1 为困境区
+const ALTMAN_SAFE: f64 = 2.6;
+const ALTMAN_DISTRESS: f64 = 1.1;
+
+/// 营运资金,未直接提供时由流动资产减流动负债得出
+fn working_capital(company: &CompanyData) -> Option<f64> {
+    company.working_capital.or_else(|| {
+        company
+            .current_assets
+            .zip(company.current_liabilities)
+            .map(|(ca, cl)| ca - cl)
+    })
+}
+
+fn total_assets(company: &CompanyData) -> Option<f64> {
+    (company.total_assets > 0.0).then_some(company.total_assets)
+}
+
+/// 标准正态分布函数,Abramowitz-Stegun 近似
+fn normal_cdf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.2316419 * x.abs());
+    let poly = t
+        * (0.319381530
+            + t * (-0.356563782 + t * (1.781477937 + t * (-1.821255978 + t * 1.330274429))));
+    let tail = (-x * x / 2.0).exp() / (2.0 * std::f64::consts::PI).sqrt() * poly;
+    if x >= 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// 适用于非上市企业的 Altman Z''
+pub fn altman_z(company: &CompanyData) -> Option<f64> {
+    let ta = total_assets(company)?;
+    let wc = working_capital(company)?;
+    let re = company.retained_earnings?;
+    let ebit = company.ebit?;
+    if company.total_liabilities <= 0.0 {
+        return None;
+    }
+    let equity = company.total_assets - company.total_liabilities;
+    Some(
+        6.56 * wc / ta
+            + 3.26 * re / ta
+            + 6.72 * ebit / ta
+            + 1.05 * equity / company.total_liabilities,
+    )
+}
+
+pub fn altman_zone(z: f64) -> &'static str {
+    if z > ALTMAN_SAFE {
+        "安全区"
+    } else if z >= ALTMAN_DISTRESS {
+        "灰色区"
+    } else {
+        "困境区"
+    }
+}
+
+/// Ohlson O-score,规模项按资产总额(百万元)取对数;
+/// 单期数据缺少前一年净利润,INTWO 与 CHIN 两项不计入
+pub fn ohlson_o(company: &CompanyData) -> Option<f64> {
+    let ta = total_assets(company)?;
+    let wc = working_capital(company)?;
+    let ca = company.current_assets.filter(|v| *v > 0.0)?;
+    let cl = company.current_liabilities?;
+    let ffo = company.operating_cash_flow?;
+    let tl = company.total_liabilities;
+    let ffo_to_tl = if tl > 0.0 { ffo / tl } else { 0.0 };
+    let oeneg = if tl > ta { 1.0 } else { 0.0 };
+    Some(
+        -1.32 - 0.407 * (ta / 100.0).ln() + 6.03 * tl / ta - 1.43 * wc / ta + 0.0757 * cl / ca
+            - 1.72 * oeneg
+            - 2.37 * company.net_profit / ta
+            - 1.83 * ffo_to_tl,
+    )
+}
+
+/// Zmijewski 概率模型得分
+pub fn zmijewski(company: &CompanyData) -> Option<f64> {
+    let ta = total_assets(company)?;
+    let ca = company.current_assets?;
+    let cl = company.current_liabilities.filter(|v| *v > 0.0)?;
+    Some(
+        -4.336 - 4.513 * company.net_profit / ta
+            + 5.679 * company.total_liabilities / ta
+            + 0.004 * ca / cl,
+    )
+}
+
+/// 计算各财务困境模型指标,所需字段缺失的模型返回空
+pub fn assess_distress(company: &CompanyData) -> DistressIndicators {
+    let altman = altman_z(company);
+    let ohlson = ohlson_o(company);
+    let zmijewski = zmijewski(company);
+    DistressIndicators {
+        altman_z: altman,
+        altman_zone: altman.map(|z| altman_zone(z).to_string()),
+        ohlson_o: ohlson,
+        ohlson_probability: ohlson.map(|o| 1.0 / (1.0 + (-o).exp())),
+        zmijewski,
+        zmijewski_probability: zmijewski.map(normal_cdf),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn company(extra: serde_json::Value) -> CompanyData {
+        let mut value = serde_json::json!({
+            "企业ID": "C1",
+            "企业名称": "甲",
+            "行业": "制造业",
+            "营业收入(万元)": 1000.0,
+            "净利润(万元)": 80.0,
+            "资产总额(万元)": 1000.0,
+            "负债总额(万元)": 400.0,
+            "资产负债率(%)": 40.0,
+            "研发投入占比(%)": 5.0,
+            "专利数量": 3,
+            "上游核心企业数量": 2,
+            "下游客户数量": 5,
+            "历史逾期次数": 0,
+            "法律诉讼次数": 0
+        });
+        value
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn complete() -> CompanyData {
+        company(serde_json::json!({
+            "留存收益(万元)": 200.0,
+            "息税前利润(万元)": 100.0,
+            "流动资产(万元)": 500.0,
+            "流动负债(万元)": 200.0,
+            "经营活动现金流量(万元)": 120.0
+        }))
+    }
+
+    #[test]
+    fn scores_all_models_from_complete_data() {
+        let indicators = assess_distress(&complete());
+
+        // 营运资金由流动资产减流动负债得出
+        assert!((indicators.altman_z.unwrap() - 4.867).abs() < 1e-9);
+        assert_eq!(indicators.altman_zone.as_deref(), Some("安全区"));
+        let o = indicators.ohlson_o.unwrap();
+        assert!((o - -0.982_472_132_848_576).abs() < 1e-9);
+        assert!((indicators.ohlson_probability.unwrap() - 1.0 / (1.0 + (-o).exp())).abs() < 1e-12);
+        assert!((indicators.zmijewski.unwrap() - -2.41544).abs() < 1e-9);
+        let p = indicators.zmijewski_probability.unwrap();
+        assert!((p - 0.007_86).abs() < 1e-4);
+    }
+
+    #[test]
+    fn missing_inputs_leave_models_empty() {
+        let indicators = assess_distress(&company(serde_json::json!({})));
+        assert!(indicators.altman_z.is_none() && indicators.altman_zone.is_none());
+        assert!(indicators.ohlson_o.is_none() && indicators.ohlson_probability.is_none());
+        assert!(indicators.zmijewski.is_none() && indicators.zmijewski_probability.is_none());
+
+        // 直接填写的营运资金优先于流动资产与流动负债之差
+        let mut explicit = complete();
+        explicit.working_capital = Some(-100.0);
+        assert!((altman_z(&explicit).unwrap() - (4.867 - 6.56 * 0.4)).abs() < 1e-9);
+
+        let mut no_assets = complete();
+        no_assets.total_assets = 0.0;
+        assert_eq!(assess_distress(&no_assets).altman_z, None);
+        assert_eq!(assess_distress(&no_assets).zmijewski, None);
+    }
+
+    #[test]
+    fn classifies_altman_zones_and_normal_tail() {
+        assert_eq!(altman_zone(2.61), "安全区");
+        assert_eq!(altman_zone(2.6), "灰色区");
+        assert_eq!(altman_zone(1.1), "灰色区");
+        assert_eq!(altman_zone(1.09), "困境区");
+
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.96) - 0.975).abs() < 1e-4);
+        assert!((normal_cdf(-1.0) + normal_cdf(1.0) - 1.0).abs() < 1e-7);
+    }
+}
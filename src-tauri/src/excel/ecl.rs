@@ -0,0 +1,437 @@
+use crate::excel::calc::{rating_notches, suggested_exposure, RATING_GRADES};
+use crate::excel::store::{earlier_limits, initial_ratings, latest_approvals};
+use crate::excel::types::{
+    CompanyEcl, CompanyWithScore, EadSource, EclResult, ExcelResult, PdSource, StageTotal,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{LazyLock, PoisonError, RwLock};
+
+/// 内置预期信用损失参数
+const DEFAULT_CONFIG: &str = include_str!("../../rules/ecl.json");
+
+static CONFIG: LazyLock<RwLock<ConfigState>> = LazyLock::new(|| {
+    RwLock::new(ConfigState {
+        path: None,
+        config: serde_json::from_str(DEFAULT_CONFIG).expect("内置预期信用损失参数格式错误"),
+    })
+});
+
+struct ConfigState {
+    path: Option<PathBuf>,
+    config: EclConfig,
+}
+
+/// 预期信用损失参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EclConfig {
+    pub pd_source: PdSource,
+    /// 评级 -> 12 个月违约概率
+    pub pd_table: BTreeMap<String, f64>,
+    /// 担保方式 -> 违约损失率
+    pub lgd_table: BTreeMap<String, f64>,
+    /// 未指定担保方式时使用
+    pub default_collateral: String,
+    /// 计算整个存续期 ECL 的剩余期限(年)
+    pub lifetime_years: f64,
+    /// 较首次评分下调达到该档数视为信用风险显著增加,划入阶段 2
+    pub stage2_downgrade_notches: u32,
+    pub stage2_overdue_count: i32,
+    /// 历史逾期达到该次数视为已发生信用减值,划入阶段 3
+    pub stage3_overdue_count: i32,
+    /// 直接划入阶段 3 的评级
+    pub stage3_ratings: Vec<String>,
+}
+
+impl EclConfig {
+    fn validate(&self) -> Result<(), String> {
+        for grade in RATING_GRADES {
+            match self.pd_table.get(grade) {
+                Some(pd) if (0.0..=1.0).contains(pd) => {}
+                Some(pd) => return Err(format!("评级 {} 的违约概率 {} 不在 0-1 之间", grade, pd)),
+                None => return Err(format!("违约概率表缺少评级 {}", grade)),
+            }
+        }
+        for (collateral, lgd) in &self.lgd_table {
+            if !(0.0..=1.0).contains(lgd) {
+                return Err(format!(
+                    "担保方式 {} 的违约损失率 {} 不在 0-1 之间",
+                    collateral, lgd
+                ));
+            }
+        }
+        if !self.lgd_table.contains_key(&self.default_collateral) {
+            return Err(format!(
+                "违约损失率表缺少默认担保方式 {}",
+                self.default_collateral
+            ));
+        }
+        if !self.lifetime_years.is_finite() || self.lifetime_years < 1.0 {
+            return Err("剩余期限应为不小于 1 年的有限数值".into());
+        }
+        if self.stage2_downgrade_notches == 0 {
+            return Err("阶段 2 的评级下调档数必须大于 0".into());
+        }
+        if self.stage2_overdue_count < 1 {
+            return Err("阶段 2 的逾期次数必须大于 0".into());
+        }
+        if self.stage2_overdue_count > self.stage3_overdue_count {
+            return Err("阶段 2 的逾期次数不能大于阶段 3".into());
+        }
+        Ok(())
+    }
+
+    /// 划分减值阶段,返回阶段及划入阶段 2、3 的原因
+    fn classify(&self, company: &CompanyWithScore, initial: Option<&str>) -> (u8, Option<String>) {
+        let rating = company.credit_rating.as_str();
+        let overdue = company.company_data.overdue_count;
+        if self.stage3_ratings.iter().any(|r| r == rating) {
+            return (3, Some(format!("评级为 {}", rating)));
+        }
+        if overdue >= self.stage3_overdue_count {
+            return (3, Some(format!("历史逾期 {} 次", overdue)));
+        }
+        if let Some(initial) = initial {
+            if rating_notches(initial, rating) <= -(self.stage2_downgrade_notches as i32) {
+                return (2, Some(format!("评级由 {} 下调至 {}", initial, rating)));
+            }
+        }
+        if overdue >= self.stage2_overdue_count {
+            return (2, Some(format!("历史逾期 {} 次", overdue)));
+        }
+        (1, None)
+    }
+
+    /// 12 个月违约概率及其实际来源,评分时没有模型违约概率的企业按评级查表
+    fn pd_12m(&self, company: &CompanyWithScore) -> Result<(f64, PdSource), String> {
+        let model_pd = match self.pd_source {
+            PdSource::Model => company.probability_of_default,
+            PdSource::Table => None,
+        };
+        match model_pd {
+            Some(pd) => Ok((pd, PdSource::Model)),
+            None => self
+                .pd_table
+                .get(&company.credit_rating)
+                .map(|pd| (*pd, PdSource::Table))
+                .ok_or_else(|| format!("违约概率表缺少评级 {}", company.credit_rating)),
+        }
+    }
+}
+
+/// 启动时加载用户保存的预期信用损失参数
+pub fn init_ecl_config(path: PathBuf) -> Result<(), String> {
+    let mut state = CONFIG.write().unwrap_or_else(PoisonError::into_inner);
+    if let Ok(content) = fs::read_to_string(&path) {
+        match parse_config(&content) {
+            Ok(config) => state.config = config,
+            Err(e) => eprintln!("加载预期信用损失参数失败: {}", e),
+        }
+    }
+    state.path = Some(path);
+    Ok(())
+}
+
+fn parse_config(content: &str) -> Result<EclConfig, String> {
+    let config: EclConfig =
+        serde_json::from_str(content).map_err(|e| format!("预期信用损失参数格式错误: {}", e))?;
+    config.validate()?;
+    Ok(config)
+}
+
+pub fn ecl_config() -> EclConfig {
+    CONFIG
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .config
+        .clone()
+}
+
+/// 替换并保存预期信用损失参数
+pub fn save_ecl_config(config: EclConfig) -> Result<(), String> {
+    config.validate()?;
+    let mut state = CONFIG.write().unwrap_or_else(PoisonError::into_inner);
+    if let Some(path) = &state.path {
+        let content = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| format!("保存预期信用损失参数失败: {}", e))?;
+    }
+    state.config = config;
+    Ok(())
+}
+
+/// 计算一次导入中各企业的 12 个月及整个存续期预期信用损失,并按阶段汇总。
+/// collateral 为 企业ID -> 担保方式,未列出的企业使用默认担保方式;
+/// 已有银行批复的企业以批复额度为风险敞口,否则使用建议授信额度;
+/// 当前评级不给予授信的企业使用此前评分中最近一次的建议授信额度,都没有时风险敞口记为未知
+pub fn calculate_ecl(
+    import_id: i64,
+    results: &[ExcelResult],
+    collateral: &HashMap<String, String>,
+    config: &EclConfig,
+) -> Result<EclResult, String> {
+    let history = EclHistory::load(import_id)?;
+    ecl_with_history(import_id, results, collateral, config, &history)
+}
+
+/// 计算时参考的往期数据,均截至该次导入
+#[derive(Default)]
+struct EclHistory {
+    /// 企业ID -> 最近一次批复额度
+    approvals: HashMap<String, f64>,
+    /// 企业ID -> 首次评分时的评级
+    initial_ratings: HashMap<String, String>,
+    /// 企业ID -> 此前评分中最近一次大于 0 的建议授信额度
+    previous_exposures: HashMap<String, f64>,
+}
+
+impl EclHistory {
+    fn load(import_id: i64) -> Result<Self, String> {
+        let mut previous_exposures = HashMap::new();
+        for (company_id, limit) in earlier_limits(import_id)? {
+            let exposure = suggested_exposure(&limit);
+            if exposure > 0.0 {
+                previous_exposures.insert(company_id, exposure);
+            }
+        }
+        Ok(EclHistory {
+            approvals: latest_approvals(import_id)?,
+            initial_ratings: initial_ratings(import_id)?,
+            previous_exposures,
+        })
+    }
+}
+
+fn ecl_with_history(
+    import_id: i64,
+    results: &[ExcelResult],
+    collateral: &HashMap<String, String>,
+    config: &EclConfig,
+    history: &EclHistory,
+) -> Result<EclResult, String> {
+    let mut companies = Vec::new();
+    for company in results.iter().flat_map(|r| &r.companies) {
+        let company_id = &company.company_data.company_id;
+        let collateral_type = collateral
+            .get(company_id)
+            .unwrap_or(&config.default_collateral);
+        let lgd = *config
+            .lgd_table
+            .get(collateral_type)
+            .ok_or_else(|| format!("未知的担保方式: {}", collateral_type))?;
+        let suggested = suggested_exposure(&company.credit_limit);
+        let (ead, ead_source) = match history.approvals.get(company_id) {
+            Some(limit) => (Some(*limit), EadSource::Approved),
+            None if suggested > 0.0 => (Some(suggested), EadSource::Suggested),
+            None => match history.previous_exposures.get(company_id) {
+                Some(exposure) => (Some(*exposure), EadSource::Previous),
+                None => (None, EadSource::Missing),
+            },
+        };
+
+        let initial_rating = history.initial_ratings.get(company_id).cloned();
+        let (stage, stage_reason) = config.classify(company, initial_rating.as_deref());
+        // 已发生信用减值的企业视为违约
+        let (pd_12m, pd_source) = if stage == 3 {
+            (1.0, None)
+        } else {
+            let (pd, source) = config.pd_12m(company)?;
+            (pd, Some(source))
+        };
+        // 假设各年违约概率相同,存续期累计违约概率 1 - (1 - PD)^T
+        let pd_lifetime = 1.0 - (1.0 - pd_12m).powf(config.lifetime_years);
+        let ecl_12m = ead.map(|ead| pd_12m * lgd * ead);
+        let ecl_lifetime = ead.map(|ead| pd_lifetime * lgd * ead);
+
+        companies.push(CompanyEcl {
+            company_id: company_id.clone(),
+            company_name: company.company_data.company_name.clone(),
+            credit_rating: company.credit_rating.clone(),
+            initial_rating,
+            stage,
+            stage_reason,
+            collateral_type: collateral_type.clone(),
+            pd_12m,
+            pd_source,
+            pd_lifetime,
+            lgd,
+            ead,
+            ead_source,
+            ecl_12m,
+            ecl_lifetime,
+            ecl: if stage == 1 { ecl_12m } else { ecl_lifetime },
+        });
+    }
+
+    let mut stages: Vec<StageTotal> = (1..=3)
+        .map(|stage| StageTotal {
+            stage,
+            count: 0,
+            ead: 0.0,
+            ecl: 0.0,
+        })
+        .collect();
+    let (mut total_ecl_12m, mut total_ecl_lifetime) = (0.0, 0.0);
+    let mut missing_ead = 0;
+    for company in &companies {
+        let total = &mut stages[usize::from(company.stage) - 1];
+        total.count += 1;
+        let (Some(ead), Some(ecl), Some(ecl_12m), Some(ecl_lifetime)) = (
+            company.ead,
+            company.ecl,
+            company.ecl_12m,
+            company.ecl_lifetime,
+        ) else {
+            missing_ead += 1;
+            continue;
+        };
+        total.ead += ead;
+        total.ecl += ecl;
+        total_ecl_12m += ecl_12m;
+        total_ecl_lifetime += ecl_lifetime;
+    }
+    let total_ead = stages.iter().fold(0.0, |sum, s| sum + s.ead);
+    let total_ecl = stages.iter().fold(0.0, |sum, s| sum + s.ecl);
+
+    Ok(EclResult {
+        import_id,
+        pd_source: config.pd_source,
+        lifetime_years: config.lifetime_years,
+        total_ead,
+        total_ecl_12m,
+        total_ecl_lifetime,
+        total_ecl,
+        coverage_ratio: if total_ead > 0.0 {
+            total_ecl / total_ead
+        } else {
+            0.0
+        },
+        missing_ead,
+        stages,
+        companies,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::excel::calc::score_company;
+    use crate::excel::scorecard::{Scorecard, DEFAULT_SCORECARD};
+    use crate::excel::types::CompanyData;
+
+    fn config() -> EclConfig {
+        parse_config(DEFAULT_CONFIG).unwrap()
+    }
+
+    fn scored(
+        scorecard: &Scorecard,
+        company_id: &str,
+        rating: &str,
+        limit: &str,
+        overdue: i32,
+    ) -> CompanyWithScore {
+        let company: CompanyData = serde_json::from_value(serde_json::json!({
+            "企业ID": company_id,
+            "企业名称": company_id,
+            "行业": "人工智能",
+            "营业收入(万元)": 1000.0,
+            "净利润(万元)": 100.0,
+            "资产总额(万元)": 3000.0,
+            "负债总额(万元)": 1200.0,
+            "资产负债率(%)": 40.0,
+            "研发投入占比(%)": 5.0,
+            "专利数量": 3,
+            "上游核心企业数量": 2,
+            "下游客户数量": 5,
+            "历史逾期次数": overdue,
+            "法律诉讼次数": 0
+        }))
+        .unwrap();
+        let mut scored = score_company(company, scorecard);
+        scored.credit_rating = rating.into();
+        scored.credit_limit = limit.into();
+        scored
+    }
+
+    #[test]
+    fn classifies_stages_and_selects_exposure() {
+        let scorecard = Scorecard::from_json(DEFAULT_SCORECARD).unwrap();
+        let companies = vec![
+            // 已有批复,以批复额度为敞口
+            scored(&scorecard, "approved", "A", "500-800万", 0),
+            // 较首次评分下调两档,划入阶段 2
+            scored(&scorecard, "downgraded", "BBB", "300-500万", 0),
+            // 逾期一次,划入阶段 2
+            scored(&scorecard, "overdue", "BBB", "300-500万", 1),
+            // 评级为 C 划入阶段 3,沿用此前的建议额度
+            scored(&scorecard, "previous", "C", "拒绝授信", 0),
+            // 需要担保且没有往期额度,敞口未知
+            scored(&scorecard, "missing", "CC", "需要担保", 3),
+        ];
+        let results = vec![ExcelResult {
+            file: "a.xlsx".into(),
+            sheet_name: "Sheet1".into(),
+            total_companies: companies.len(),
+            scorecard: scorecard.id(),
+            companies,
+            peer_stats: Vec::new(),
+        }];
+        let history = EclHistory {
+            approvals: HashMap::from([("approved".to_string(), 300.0)]),
+            initial_ratings: HashMap::from([("downgraded".to_string(), "AA".to_string())]),
+            previous_exposures: HashMap::from([("previous".to_string(), 200.0)]),
+        };
+        let collateral = HashMap::from([("approved".to_string(), "存单质押".to_string())]);
+
+        let result = ecl_with_history(1, &results, &collateral, &config(), &history).unwrap();
+
+        let by_id = |id: &str| {
+            result
+                .companies
+                .iter()
+                .find(|c| c.company_id == id)
+                .unwrap()
+        };
+        let stages: Vec<u8> = result.companies.iter().map(|c| c.stage).collect();
+        assert_eq!(stages, [1, 2, 2, 3, 3]);
+
+        let approved = by_id("approved");
+        assert_eq!(
+            (approved.ead, approved.ead_source),
+            (Some(300.0), EadSource::Approved)
+        );
+        assert_eq!(approved.ecl, Some(0.0015 * 0.05 * 300.0));
+        let downgraded = by_id("downgraded");
+        assert_eq!(
+            (downgraded.ead, downgraded.ead_source),
+            (Some(400.0), EadSource::Suggested)
+        );
+        assert_eq!(downgraded.ecl, downgraded.ecl_lifetime);
+        let previous = by_id("previous");
+        assert_eq!(
+            (previous.ead, previous.ead_source),
+            (Some(200.0), EadSource::Previous)
+        );
+        assert_eq!((previous.pd_12m, previous.pd_source), (1.0, None));
+        assert_eq!(previous.ecl, Some(0.45 * 200.0));
+        let missing = by_id("missing");
+        assert_eq!((missing.ead, missing.ecl), (None, None));
+
+        assert_eq!(result.missing_ead, 1);
+        assert_eq!(result.total_ead, 300.0 + 400.0 + 400.0 + 200.0);
+        assert_eq!(result.stages[2].count, 2);
+        assert_eq!(result.stages[2].ead, 200.0);
+    }
+
+    #[test]
+    fn rejects_unbounded_lifetime_and_zero_overdue_threshold() {
+        let mut infinite = config();
+        infinite.lifetime_years = f64::INFINITY;
+        assert!(infinite.validate().is_err());
+
+        let mut zero = config();
+        zero.stage2_overdue_count = 0;
+        assert!(zero.validate().is_err());
+    }
+}
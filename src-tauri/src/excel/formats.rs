@@ -0,0 +1,245 @@
+use crate::excel::cell::CellValue;
+use serde_json::{Map, Value};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// 支持导入的文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Excel,
+    Csv,
+    Json,
+    JsonLines,
+}
+
+impl FileFormat {
+    /// 按扩展名识别文件格式,其余均按 Excel 处理
+    pub fn detect(path: &str) -> FileFormat {
+        let ext = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("csv") => FileFormat::Csv,
+            Some("json") => FileFormat::Json,
+            Some("jsonl") | Some("ndjson") => FileFormat::JsonLines,
+            _ => FileFormat::Excel,
+        }
+    }
+}
+
+/// 一行数据及其在文件中的行号
+pub type Row = (usize, Vec<CellValue>);
+
+/// 从文本文件读取的一张数据表,行按需逐条读取
+pub struct Table {
+    pub name: String,
+    pub headers: Vec<String>,
+    pub rows: Box<dyn Iterator<Item = Result<Row, String>>>,
+}
+
+fn file_stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn open(path: &str) -> Result<File, String> {
+    File::open(path).map_err(|e| format!("无法打开文件 {}: {}", path, e))
+}
+
+/// 读取 CSV、JSON 或 JSON Lines 文件
+pub fn read_tables(path: &str, format: FileFormat) -> Result<Vec<Table>, String> {
+    match format {
+        FileFormat::Csv => Ok(vec![read_csv(path)?]),
+        FileFormat::Json => read_json(path),
+        FileFormat::JsonLines => Ok(vec![read_json_lines(path)?]),
+        FileFormat::Excel => Err(format!("{} 不是文本格式文件", path)),
+    }
+}
+
+/// CSV 首行为表头,以文件名作为表名
+fn read_csv(path: &str) -> Result<Table, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(open(path)?);
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("读取 {} 表头失败: {}", path, e))?
+        .iter()
+        .map(|h| h.trim_start_matches('\u{feff}').to_string())
+        .collect();
+
+    let file = path.to_string();
+    let rows = reader.into_records().enumerate().map(move |(i, record)| {
+        let record = record.map_err(|e| format!("读取 {} 第 {} 行失败: {}", file, i + 2, e))?;
+        let line = record.position().map_or(i + 2, |p| p.line() as usize);
+        let cells = record
+            .iter()
+            .map(|v| CellValue::Text(v.to_string()))
+            .collect();
+        Ok((line, cells))
+    });
+
+    Ok(Table {
+        name: file_stem(path),
+        headers,
+        rows: Box::new(rows),
+    })
+}
+
+/// JSON 记录及其序号或行号
+type JsonRecord = (usize, Map<String, Value>);
+
+fn cell_from_json(value: &Value) -> CellValue {
+    match value {
+        Value::Null => CellValue::Empty,
+        Value::Bool(b) => CellValue::Bool(*b),
+        Value::Number(n) => n.as_f64().map_or(CellValue::Empty, CellValue::Number),
+        Value::String(s) => CellValue::Text(s.clone()),
+        other => CellValue::Text(other.to_string()),
+    }
+}
+
+fn cells_from_object(headers: &[String], object: &Map<String, Value>) -> Vec<CellValue> {
+    headers
+        .iter()
+        .map(|h| object.get(h).map_or(CellValue::Empty, cell_from_json))
+        .collect()
+}
+
+/// 将 JSON 对象数组转换为表,表头为所有对象键的并集
+fn table_from_objects(name: String, objects: Vec<JsonRecord>) -> Table {
+    let mut headers: Vec<String> = Vec::new();
+    for (_, object) in &objects {
+        for key in object.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+    let columns = headers.clone();
+    let rows = objects
+        .into_iter()
+        .map(move |(line, object)| Ok((line, cells_from_object(&columns, &object))));
+    Table {
+        name,
+        headers,
+        rows: Box::new(rows),
+    }
+}
+
+fn objects(values: Vec<Value>, context: &str) -> Result<Vec<JsonRecord>, String> {
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| match value {
+            Value::Object(object) => Ok((i + 1, object)),
+            _ => Err(format!("{} 第 {} 条记录不是 JSON 对象", context, i + 1)),
+        })
+        .collect()
+}
+
+/// JSON 文件可为对象数组,或以表名为键、对象数组为值的对象;
+/// 行号为记录在数组中的序号。JSON 需整体解析,大文件建议使用 JSON Lines
+fn read_json(path: &str) -> Result<Vec<Table>, String> {
+    let value: Value = serde_json::from_reader(BufReader::new(open(path)?))
+        .map_err(|e| format!("JSON 格式错误 {}: {}", path, e))?;
+    match value {
+        Value::Array(values) => Ok(vec![table_from_objects(
+            file_stem(path),
+            objects(values, path)?,
+        )]),
+        Value::Object(sheets) => sheets
+            .into_iter()
+            .map(|(name, values)| match values {
+                Value::Array(values) => {
+                    let objects = objects(values, &name)?;
+                    Ok(table_from_objects(name, objects))
+                }
+                _ => Err(format!("{} 中 {} 的值需为企业对象数组", path, name)),
+            })
+            .collect(),
+        _ => Err(format!("{} 需为企业对象数组", path)),
+    }
+}
+
+/// 解析 JSON Lines 中的一行,空行返回 None
+fn parse_json_line(path: &str, line_no: usize, line: &str) -> Result<Option<JsonRecord>, String> {
+    let line = line.trim().trim_start_matches('\u{feff}');
+    if line.is_empty() {
+        return Ok(None);
+    }
+    match serde_json::from_str(line) {
+        Ok(Value::Object(object)) => Ok(Some((line_no, object))),
+        Ok(_) => Err(format!("{} 第 {} 行不是 JSON 对象", path, line_no)),
+        Err(e) => Err(format!("{} 第 {} 行 JSON 格式错误: {}", path, line_no, e)),
+    }
+}
+
+/// 逐行读取 JSON Lines 中的记录,空行忽略
+fn json_lines(path: &str) -> Result<impl Iterator<Item = Result<JsonRecord, String>>, String> {
+    let file = path.to_string();
+    Ok(BufReader::new(open(path)?)
+        .lines()
+        .enumerate()
+        .filter_map(move |(i, line)| {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(format!("读取 {} 第 {} 行失败: {}", file, i + 1, e))),
+            };
+            parse_json_line(&file, i + 1, &line).transpose()
+        }))
+}
+
+/// JSON Lines 每行一个企业对象,表头为所有记录键的并集。
+/// 先扫描一遍收集表头,再逐行读取数据,不把整个文件载入内存
+fn read_json_lines(path: &str) -> Result<Table, String> {
+    let mut headers: Vec<String> = Vec::new();
+    for record in json_lines(path)? {
+        for key in record?.1.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+    let columns = headers.clone();
+    let rows = json_lines(path)?.map(move |record| {
+        record.map(|(line, object)| (line, cells_from_object(&columns, &object)))
+    });
+
+    Ok(Table {
+        name: file_stem(path),
+        headers,
+        rows: Box::new(rows),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_lines_headers_include_keys_missing_from_first_record() {
+        let path = std::env::temp_dir().join(format!("formats-{}.jsonl", std::process::id()));
+        std::fs::write(
+            &path,
+            "{\"企业ID\":\"L1\",\"营业收入(万元)\":500}\n\n{\"企业ID\":\"L2\",\"营业收入(万元)\":600,\"流动资产(万元)\":300}\n",
+        )
+        .unwrap();
+        let table = read_json_lines(path.to_str().unwrap()).unwrap();
+        let rows: Vec<Row> = table.rows.collect::<Result<_, _>>().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            table.headers,
+            ["企业ID", "营业收入(万元)", "流动资产(万元)"]
+        );
+        assert_eq!(rows[0].0, 1);
+        assert_eq!(rows[0].1[2], CellValue::Empty);
+        assert_eq!(rows[1].0, 3);
+        assert_eq!(rows[1].1[2], CellValue::Number(300.0));
+    }
+}
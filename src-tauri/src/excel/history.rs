@@ -0,0 +1,271 @@
+use crate::excel::types::{CompanyData, FinancialPeriod};
+use std::collections::HashMap;
+
+/// 从工作表名称中识别年度,如 "2023"、"2023年财务数据"
+pub fn fiscal_year_from_sheet(sheet_name: &str) -> Option<i32> {
+    let digits: Vec<char> = sheet_name.chars().collect();
+    digits.windows(4).enumerate().find_map(|(i, w)| {
+        let boundary_before = i == 0 || !digits[i - 1].is_ascii_digit();
+        let boundary_after = digits.get(i + 4).is_none_or(|c| !c.is_ascii_digit());
+        if !(boundary_before && boundary_after && w.iter().all(|c| c.is_ascii_digit())) {
+            return None;
+        }
+        let year: i32 = w.iter().collect::<String>().parse().ok()?;
+        (1900..=2100).contains(&year).then_some(year)
+    })
+}
+
+fn period(company: &CompanyData, fiscal_year: i32) -> FinancialPeriod {
+    FinancialPeriod {
+        fiscal_year,
+        revenue: company.revenue,
+        net_profit: company.net_profit,
+        total_assets: company.total_assets,
+        total_liabilities: company.total_liabilities,
+        debt_to_asset_ratio: company.debt_to_asset_ratio,
+        r_and_d_ratio: company.r_and_d_ratio,
+    }
+}
+
+/// 合并同一企业ID的多个年度记录:以最新年度作为评分快照,
+/// 各年度数据按年份升序写入 history。未标注年度的记录保持原样
+pub fn merge_fiscal_years<T>(entries: Vec<(T, CompanyData)>) -> Vec<(T, CompanyData)> {
+    let mut latest: HashMap<String, usize> = HashMap::new();
+    let mut periods: HashMap<String, Vec<FinancialPeriod>> = HashMap::new();
+    for (i, (_, company)) in entries.iter().enumerate() {
+        let Some(year) = company.fiscal_year else {
+            continue;
+        };
+        if company.company_id.is_empty() {
+            continue;
+        }
+        periods
+            .entry(company.company_id.clone())
+            .or_default()
+            .push(period(company, year));
+        let current = latest.entry(company.company_id.clone()).or_insert(i);
+        if entries[*current].1.fiscal_year <= Some(year) {
+            *current = i;
+        }
+    }
+
+    entries
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, (location, mut company))| {
+            if company.fiscal_year.is_none() || company.company_id.is_empty() {
+                return Some((location, company));
+            }
+            if latest.get(&company.company_id) != Some(&i) {
+                return None;
+            }
+            let mut history = periods.remove(&company.company_id).unwrap_or_default();
+            history.sort_by_key(|p| p.fiscal_year);
+            // 同一年度重复出现时保留最后一条
+            history.dedup_by(|later, earlier| {
+                if later.fiscal_year == earlier.fiscal_year {
+                    std::mem::swap(later, earlier);
+                    true
+                } else {
+                    false
+                }
+            });
+            company.history = history;
+            Some((location, company))
+        })
+        .collect()
+}
+
+/// 用评分快照更新同一年度的最新一期历史数据,
+/// 快照被修改(如压力测试施加冲击)后,多期趋势因子随之反映修改
+pub fn sync_latest_period(company: &mut CompanyData) {
+    let Some(year) = company.fiscal_year else {
+        return;
+    };
+    let snapshot = period(company, year);
+    if let Some(latest) = company.history.last_mut().filter(|p| p.fiscal_year == year) {
+        *latest = snapshot;
+    }
+}
+
+/// 按年份升序取有效的多期数据,不足两期时返回 None
+fn multi_period(company: &CompanyData) -> Option<&[FinancialPeriod]> {
+    (company.history.len() >= 2).then_some(company.history.as_slice())
+}
+
+/// 营业收入复合增长率
+pub fn revenue_cagr(company: &CompanyData) -> Option<f64> {
+    let history = multi_period(company)?;
+    let first = history.iter().find(|p| p.revenue > 0.0)?;
+    let last = history.last()?;
+    let years = last.fiscal_year - first.fiscal_year;
+    if years <= 0 || last.revenue < 0.0 {
+        return None;
+    }
+    Some((last.revenue / first.revenue).powf(1.0 / years as f64) - 1.0)
+}
+
+/// 净利润率年均变化(百分点/年),按最小二乘斜率计算
+pub fn margin_trend(company: &CompanyData) -> Option<f64> {
+    let points: Vec<(f64, f64)> = multi_period(company)?
+        .iter()
+        .filter(|p| p.revenue > 0.0)
+        .map(|p| (p.fiscal_year as f64, p.net_profit / p.revenue * 100.0))
+        .collect();
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let sxy: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    (sxx > 0.0).then(|| sxy / sxx)
+}
+
+/// 资产负债率变化(百分点),最新年度减最早年度
+pub fn debt_ratio_change(company: &CompanyData) -> Option<f64> {
+    let history = multi_period(company)?;
+    Some(history.last()?.debt_to_asset_ratio - history.first()?.debt_to_asset_ratio)
+}
+
+/// 营业收入同比增长率的标准差,至少需要三期数据
+pub fn revenue_volatility(company: &CompanyData) -> Option<f64> {
+    let growth: Vec<f64> = multi_period(company)?
+        .windows(2)
+        .filter(|w| w[0].revenue > 0.0)
+        .map(|w| w[1].revenue / w[0].revenue - 1.0)
+        .collect();
+    if growth.len() < 2 {
+        return None;
+    }
+    let mean = growth.iter().sum::<f64>() / growth.len() as f64;
+    let variance =
+        growth.iter().map(|g| (g - mean).powi(2)).sum::<f64>() / (growth.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn company(id: &str, fiscal_year: Option<i32>, revenue: f64, debt_ratio: f64) -> CompanyData {
+        serde_json::from_value(serde_json::json!({
+            "企业ID": id,
+            "企业名称": "甲",
+            "行业": "制造业",
+            "营业收入(万元)": revenue,
+            "净利润(万元)": revenue / 10.0,
+            "资产总额(万元)": 2000.0,
+            "负债总额(万元)": 20.0 * debt_ratio,
+            "资产负债率(%)": debt_ratio,
+            "研发投入占比(%)": 5.0,
+            "专利数量": 3,
+            "上游核心企业数量": 2,
+            "下游客户数量": 5,
+            "历史逾期次数": 0,
+            "法律诉讼次数": 0,
+            "年度": fiscal_year
+        }))
+        .unwrap()
+    }
+
+    fn with_history(periods: &[(i32, f64, f64, f64)]) -> CompanyData {
+        let mut company = company("C1", None, 0.0, 0.0);
+        company.history = periods
+            .iter()
+            .map(
+                |&(fiscal_year, revenue, net_profit, debt_to_asset_ratio)| FinancialPeriod {
+                    fiscal_year,
+                    revenue,
+                    net_profit,
+                    total_assets: 2000.0,
+                    total_liabilities: 20.0 * debt_to_asset_ratio,
+                    debt_to_asset_ratio,
+                    r_and_d_ratio: 5.0,
+                },
+            )
+            .collect();
+        company
+    }
+
+    #[test]
+    fn reads_fiscal_year_from_sheet_names() {
+        assert_eq!(fiscal_year_from_sheet("2023"), Some(2023));
+        assert_eq!(fiscal_year_from_sheet("2022年财务数据"), Some(2022));
+        assert_eq!(fiscal_year_from_sheet("Sheet12345"), None);
+        assert_eq!(fiscal_year_from_sheet("3000年"), None);
+        assert_eq!(fiscal_year_from_sheet("数据"), None);
+    }
+
+    #[test]
+    fn merges_years_into_latest_snapshot() {
+        let merged = merge_fiscal_years(vec![
+            (1, company("C1", Some(2023), 1200.0, 40.0)),
+            (2, company("C1", Some(2021), 1000.0, 50.0)),
+            (3, company("C2", None, 800.0, 30.0)),
+            (4, company("C1", Some(2022), 1100.0, 45.0)),
+            (5, company("C1", Some(2022), 1150.0, 44.0)),
+        ]);
+
+        assert_eq!(merged.len(), 2);
+        let (location, latest) = &merged[0];
+        assert_eq!(*location, 1);
+        assert_eq!(latest.fiscal_year, Some(2023));
+        let years: Vec<i32> = latest.history.iter().map(|p| p.fiscal_year).collect();
+        assert_eq!(years, [2021, 2022, 2023]);
+        // 同一年度重复时取后出现的记录
+        assert_eq!(latest.history[1].revenue, 1150.0);
+        assert_eq!(merged[1].0, 3);
+        assert!(merged[1].1.history.is_empty());
+    }
+
+    #[test]
+    fn computes_trends_across_periods() {
+        let company = with_history(&[
+            (2021, 1000.0, 50.0, 60.0),
+            (2022, 1100.0, 77.0, 55.0),
+            (2023, 1210.0, 108.9, 50.0),
+        ]);
+
+        assert!((revenue_cagr(&company).unwrap() - 0.1).abs() < 1e-9);
+        // 净利润率 5% -> 7% -> 9%,每年提高 2 个百分点
+        assert!((margin_trend(&company).unwrap() - 2.0).abs() < 1e-9);
+        assert_eq!(debt_ratio_change(&company), Some(-10.0));
+        // 两期同比增长均为 10%,波动为 0
+        assert!(revenue_volatility(&company).unwrap().abs() < 1e-9);
+
+        let uneven = with_history(&[
+            (2021, 1000.0, 0.0, 0.0),
+            (2022, 1200.0, 0.0, 0.0),
+            (2023, 1080.0, 0.0, 0.0),
+        ]);
+        let expected = ((0.2f64 - 0.05).powi(2) * 2.0).sqrt();
+        assert!((revenue_volatility(&uneven).unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn trends_need_enough_usable_periods() {
+        let single = with_history(&[(2023, 1000.0, 50.0, 60.0)]);
+        assert_eq!(revenue_cagr(&single), None);
+        assert_eq!(margin_trend(&single), None);
+        assert_eq!(debt_ratio_change(&single), None);
+
+        let two = with_history(&[(2022, 1000.0, 50.0, 60.0), (2023, 1100.0, 60.0, 55.0)]);
+        assert_eq!(revenue_volatility(&two), None);
+
+        // 首期收入为零时从第一个正收入年度起算
+        let from_zero = with_history(&[
+            (2021, 0.0, 0.0, 60.0),
+            (2022, 1000.0, 50.0, 60.0),
+            (2023, 1100.0, 60.0, 55.0),
+        ]);
+        assert!((revenue_cagr(&from_zero).unwrap() - 0.1).abs() < 1e-9);
+        let no_revenue = with_history(&[(2022, 0.0, -10.0, 60.0), (2023, 0.0, -5.0, 55.0)]);
+        assert_eq!(revenue_cagr(&no_revenue), None);
+        assert_eq!(margin_trend(&no_revenue), None);
+    }
+}
@@ -0,0 +1,351 @@
+use crate::excel::cell::{is_percent, split_fraction_unit};
+use crate::excel::formats::{read_tables, FileFormat};
+use crate::excel::reader::xlsx_rows;
+use crate::excel::types::{ColumnMapping, MatchSource, SheetMapping, SourceMapping, Unit};
+use crate::excel::units::{is_monetary, split_unit};
+use crate::excel::validate::missing_columns;
+use calamine::{open_workbook_auto, Data, Reader, Sheets};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{LazyLock, PoisonError, RwLock};
+
+/// 标准列名及内置别名
+const CANONICAL_HEADERS: &[(&str, &[&str])] = &[
+    (
+        "企业ID",
+        &["企业编号", "公司ID", "公司编号", "company_id", "id"],
+    ),
+    (
+        "企业名称",
+        &["公司名称", "企业名", "company_name", "company", "name"],
+    ),
+    ("行业", &["所属行业", "行业分类", "industry"]),
+    (
+        "营业收入(万元)",
+        &["营业收入", "营收", "revenue", "operating_revenue"],
+    ),
+    ("净利润(万元)", &["净利润", "net_profit", "net_income"]),
+    (
+        "资产总额(万元)",
+        &["资产总额", "总资产", "total_assets", "assets"],
+    ),
+    (
+        "负债总额(万元)",
+        &["负债总额", "总负债", "total_liabilities", "liabilities"],
+    ),
+    (
+        "资产负债率(%)",
+        &[
+            "资产负债率",
+            "debt_to_asset_ratio",
+            "debt_ratio",
+            "debt_to_assets",
+        ],
+    ),
+    (
+        "研发投入占比(%)",
+        &[
+            "研发投入占比",
+            "研发占比",
+            "研发费用率",
+            "r_and_d_ratio",
+            "rd_ratio",
+        ],
+    ),
+    ("专利数量", &["专利数", "patent_count", "patents"]),
+    (
+        "上游核心企业数量",
+        &[
+            "上游核心企业数",
+            "upstream_core_companies",
+            "upstream_suppliers",
+        ],
+    ),
+    (
+        "下游客户数量",
+        &["下游客户数", "downstream_customers", "customers"],
+    ),
+    ("历史逾期次数", &["逾期次数", "overdue_count", "overdues"]),
+    (
+        "法律诉讼次数",
+        &[
+            "诉讼次数",
+            "legal_disputes_count",
+            "lawsuits",
+            "legal_disputes",
+        ],
+    ),
+    ("营运资金(万元)", &["营运资金", "working_capital"]),
+    ("留存收益(万元)", &["留存收益", "retained_earnings"]),
+    ("息税前利润(万元)", &["息税前利润", "ebit"]),
+    ("流动资产(万元)", &["流动资产", "current_assets"]),
+    ("流动负债(万元)", &["流动负债", "current_liabilities"]),
+    (
+        "经营活动现金流量(万元)",
+        &["经营活动现金流量", "经营现金流", "operating_cash_flow"],
+    ),
+    ("年度", &["会计年度", "财务年度", "fiscal_year", "year"]),
+    // 训练违约概率模型和回测用的样本列,导入评分时忽略
+    (
+        "是否违约",
+        &["违约标志", "违约", "default", "default_flag", "defaulted"],
+    ),
+];
+
+static USER_MAPPINGS: LazyLock<RwLock<MappingStore>> = LazyLock::new(|| {
+    RwLock::new(MappingStore {
+        path: None,
+        sources: HashMap::new(),
+    })
+});
+
+/// 按数据来源保存的用户自定义列映射
+struct MappingStore {
+    path: Option<PathBuf>,
+    sources: HashMap<String, SourceMapping>,
+}
+
+/// 表头映射结果:标准化后的列名,需要换算的金额列单位,以及按小数填写的百分比列
+pub struct HeaderLayout {
+    pub names: Vec<String>,
+    pub units: Vec<(String, Unit)>,
+    pub fractions: Vec<String>,
+}
+
+/// 规范化列名:全角转半角,去除空白、下划线和连字符,统一小写
+pub fn normalize_header(header: &str) -> String {
+    header
+        .chars()
+        .map(|c| match c {
+            '\u{3000}' => ' ',
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .filter(|c| !c.is_whitespace() && *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 标准列名对应的所有规范化别名
+static ALIASES: LazyLock<HashMap<String, (&'static str, MatchSource)>> = LazyLock::new(|| {
+    let mut aliases = HashMap::new();
+    for (canonical, names) in CANONICAL_HEADERS {
+        for name in names.iter() {
+            aliases
+                .entry(normalize_header(name))
+                .or_insert((*canonical, MatchSource::Alias));
+        }
+        aliases.insert(
+            normalize_header(canonical),
+            (*canonical, MatchSource::Exact),
+        );
+    }
+    aliases
+});
+
+pub fn is_canonical(header: &str) -> bool {
+    CANONICAL_HEADERS.iter().any(|(c, _)| *c == header)
+}
+
+/// 解析表头,返回每一列对应的标准列名;同一标准列出现多次时仅第一列生效。
+/// 金额列的单位优先取用户声明,其次取列名末尾括号中的单位,默认为万元;
+/// 百分比列名末尾标注(小数)时同样按去掉单位后的名称匹配
+pub fn resolve_headers(headers: &[String], source: Option<&str>) -> Vec<ColumnMapping> {
+    let store = USER_MAPPINGS.read().unwrap_or_else(PoisonError::into_inner);
+    let user = source.and_then(|s| store.sources.get(s));
+    let user_columns: HashMap<String, &String> = user
+        .map(|m| {
+            m.columns
+                .iter()
+                .map(|(k, v)| (normalize_header(k), v))
+                .collect()
+        })
+        .unwrap_or_default();
+    let user_units: HashMap<String, Unit> = user
+        .map(|m| {
+            m.units
+                .iter()
+                .map(|(k, v)| (normalize_header(k), *v))
+                .collect()
+        })
+        .unwrap_or_default();
+    let lookup = |name: &str| match user_columns.get(name) {
+        Some(canonical) => Some((canonical.to_string(), MatchSource::User)),
+        None => ALIASES
+            .get(name)
+            .map(|(canonical, how)| (canonical.to_string(), *how)),
+    };
+
+    let mut used = HashSet::new();
+    headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| {
+            let normalized = normalize_header(header);
+            let suffix = split_unit(&normalized);
+            // 带单位的列名如 "营业收入(元)" 按去掉单位后的名称匹配金额列
+            let matched = lookup(&normalized)
+                .or_else(|| {
+                    let (base, _) = suffix?;
+                    lookup(base).filter(|(canonical, _)| is_monetary(canonical))
+                })
+                .or_else(|| {
+                    let base = split_fraction_unit(&normalized)?;
+                    lookup(base).filter(|(canonical, _)| is_percent(canonical))
+                });
+            let (field, matched_by) = match matched {
+                Some((canonical, _)) if used.contains(&canonical) => (None, MatchSource::Duplicate),
+                Some((canonical, how)) => {
+                    used.insert(canonical.clone());
+                    (Some(canonical), how)
+                }
+                None => (None, MatchSource::Unmatched),
+            };
+            let unit = field.as_deref().filter(|f| is_monetary(f)).map(|_| {
+                user_units
+                    .get(&normalized)
+                    .copied()
+                    .or(suffix.map(|(_, unit)| unit))
+                    .unwrap_or(Unit::TenThousand)
+            });
+            ColumnMapping {
+                index,
+                header: header.trim().to_string(),
+                field,
+                matched_by,
+                unit,
+            }
+        })
+        .collect()
+}
+
+/// 将表头替换为标准列名,未识别的列保留原名
+pub fn map_headers(headers: &[String], source: Option<&str>) -> HeaderLayout {
+    let mut units = Vec::new();
+    let mut fractions = Vec::new();
+    let names = resolve_headers(headers, source)
+        .into_iter()
+        .map(|m| match m.field {
+            Some(field) => {
+                if let Some(unit) = m.unit.filter(|u| *u != Unit::TenThousand) {
+                    units.push((field.clone(), unit));
+                }
+                if is_percent(&field) && split_fraction_unit(&normalize_header(&m.header)).is_some()
+                {
+                    fractions.push(field.clone());
+                }
+                field
+            }
+            None => m.header,
+        })
+        .collect();
+    HeaderLayout {
+        names,
+        units,
+        fractions,
+    }
+}
+
+/// 导入前预览各工作表的表头识别结果,只读取表头行
+pub fn preview_header_mapping(
+    paths: &[String],
+    source: Option<&str>,
+) -> Result<Vec<SheetMapping>, String> {
+    let mut previews = Vec::new();
+    for path in paths {
+        for (sheet_name, headers) in sheet_headers(path)? {
+            let columns = resolve_headers(&headers, source);
+            let mapped: Vec<String> = columns.iter().filter_map(|c| c.field.clone()).collect();
+            previews.push(SheetMapping {
+                file: path.clone(),
+                sheet_name,
+                missing: missing_columns(&mapped)
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                columns,
+            });
+        }
+    }
+    Ok(previews)
+}
+
+/// 文件中各工作表的名称及原始表头
+fn sheet_headers(path: &str) -> Result<Vec<(String, Vec<String>)>, String> {
+    let format = FileFormat::detect(path);
+    if format != FileFormat::Excel {
+        let tables = read_tables(path, format)?;
+        return Ok(tables.into_iter().map(|t| (t.name, t.headers)).collect());
+    }
+
+    let mut workbook =
+        open_workbook_auto(path).map_err(|e| format!("无法打开文件 {}: {}", path, e))?;
+    let mut sheets = Vec::new();
+    for sheet_name in workbook.sheet_names().to_owned() {
+        // xlsx 只流式读取首行,其余格式整表读取
+        let header_row = if let Sheets::Xlsx(xlsx) = &mut workbook {
+            xlsx_rows(xlsx, &sheet_name)?
+                .next()
+                .transpose()?
+                .map(|(_, row)| row)
+        } else {
+            let range = workbook
+                .worksheet_range(&sheet_name)
+                .map_err(|e| format!("读取工作表 {} 失败: {}", sheet_name, e))?;
+            range.rows().next().map(<[Data]>::to_vec)
+        };
+        if let Some(header_row) = header_row {
+            let headers = header_row.iter().map(|c| c.to_string()).collect();
+            sheets.push((sheet_name, headers));
+        }
+    }
+    Ok(sheets)
+}
+
+/// 启动时加载已保存的列映射
+pub fn init_header_mappings(path: PathBuf) -> Result<(), String> {
+    let mut store = USER_MAPPINGS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Ok(content) = fs::read_to_string(&path) {
+        match serde_json::from_str(&content) {
+            Ok(sources) => store.sources = sources,
+            Err(e) => eprintln!("加载列映射失败: {}", e),
+        }
+    }
+    store.path = Some(path);
+    Ok(())
+}
+
+pub fn header_mappings() -> HashMap<String, SourceMapping> {
+    USER_MAPPINGS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .sources
+        .clone()
+}
+
+/// 保存指定数据来源的列映射与单位声明,两者均为空时删除该来源
+pub fn save_header_mapping(source: &str, mapping: SourceMapping) -> Result<(), String> {
+    if source.trim().is_empty() {
+        return Err("数据来源名称不能为空".into());
+    }
+    if let Some(field) = mapping.columns.values().find(|f| !is_canonical(f)) {
+        return Err(format!("未知的标准列名: {}", field));
+    }
+
+    let mut store = USER_MAPPINGS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+    if mapping.columns.is_empty() && mapping.units.is_empty() {
+        store.sources.remove(source);
+    } else {
+        store.sources.insert(source.to_string(), mapping);
+    }
+    if let Some(path) = &store.path {
+        let content = serde_json::to_string_pretty(&store.sources).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| format!("保存列映射失败: {}", e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,239 @@
+use quick_xml::encoding::Decoder;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::BufReader;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+/// 内置的百分比数字格式 0% 与 0.00%
+const BUILTIN_PERCENT_FORMATS: [u32; 2] = [9, 10];
+
+type Archive = ZipArchive<BufReader<File>>;
+
+/// xlsx 工作表中 columns 列里设置了百分比数字格式的单元格,元素为 (行号, 列号),
+/// 行号从 1 开始,列号从 0 开始。calamine 读取时不保留数字格式,需直接解析文件
+pub fn percent_cells(
+    path: &str,
+    sheet_name: &str,
+    columns: &[usize],
+) -> Result<HashSet<(usize, usize)>, String> {
+    let mut cells = HashSet::new();
+    if columns.is_empty() {
+        return Ok(cells);
+    }
+    let file = File::open(path).map_err(|e| format!("无法打开文件 {}: {}", path, e))?;
+    let mut archive =
+        ZipArchive::new(BufReader::new(file)).map_err(|e| format!("读取 {} 失败: {}", path, e))?;
+    let styles = percent_styles(&mut archive)?;
+    if styles.is_empty() {
+        return Ok(cells);
+    }
+    let Some(sheet_path) = sheet_path(&mut archive, sheet_name)? else {
+        return Ok(cells);
+    };
+    for_each_event(&mut archive, &sheet_path, |event, decoder| {
+        let (Event::Start(e) | Event::Empty(e)) = event else {
+            return Ok(());
+        };
+        if e.local_name().as_ref() != b"c" {
+            return Ok(());
+        }
+        let (Some(reference), Some(style)) = (attr(e, b"r", decoder)?, attr(e, b"s", decoder)?)
+        else {
+            return Ok(());
+        };
+        if let Some((row, col)) = cell_position(&reference) {
+            if columns.contains(&col) && style.parse().is_ok_and(|s| styles.contains(&s)) {
+                cells.insert((row, col));
+            }
+        }
+        Ok(())
+    })?;
+    Ok(cells)
+}
+
+/// 使用百分比数字格式的单元格样式序号(cellXfs 中的位置)
+fn percent_styles(archive: &mut Archive) -> Result<HashSet<usize>, String> {
+    let mut custom: HashMap<u32, String> = HashMap::new();
+    let mut cell_formats: Vec<u32> = Vec::new();
+    let mut in_cell_xfs = false;
+    for_each_event(archive, "xl/styles.xml", |event, decoder| {
+        match event {
+            Event::Start(e) if e.local_name().as_ref() == b"cellXfs" => in_cell_xfs = true,
+            Event::End(e) if e.local_name().as_ref() == b"cellXfs" => in_cell_xfs = false,
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"numFmt" => {
+                if let (Some(id), Some(code)) = (
+                    attr(e, b"numFmtId", decoder)?,
+                    attr(e, b"formatCode", decoder)?,
+                ) {
+                    if let Ok(id) = id.parse() {
+                        custom.insert(id, code);
+                    }
+                }
+            }
+            Event::Start(e) | Event::Empty(e)
+                if in_cell_xfs && e.local_name().as_ref() == b"xf" =>
+            {
+                let id = attr(e, b"numFmtId", decoder)?.and_then(|id| id.parse().ok());
+                cell_formats.push(id.unwrap_or(0));
+            }
+            _ => {}
+        }
+        Ok(())
+    })?;
+    Ok(cell_formats
+        .iter()
+        .enumerate()
+        .filter(|(_, id)| {
+            BUILTIN_PERCENT_FORMATS.contains(id)
+                || custom.get(id).is_some_and(|code| is_percent_format(code))
+        })
+        .map(|(i, _)| i)
+        .collect())
+}
+
+/// 格式代码中引号和转义之外出现 % 即为百分比格式,如 0.0%、#,##0.00%
+fn is_percent_format(code: &str) -> bool {
+    let mut quoted = false;
+    let mut chars = code.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' => {
+                chars.next();
+            }
+            '%' if !quoted => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// 工作表在压缩包中的路径,通过 workbook.xml 中的关系 ID 查找
+fn sheet_path(archive: &mut Archive, sheet_name: &str) -> Result<Option<String>, String> {
+    let mut relationship = None;
+    for_each_event(archive, "xl/workbook.xml", |event, decoder| {
+        if let Event::Start(e) | Event::Empty(e) = event {
+            if e.local_name().as_ref() == b"sheet"
+                && attr(e, b"name", decoder)?.as_deref() == Some(sheet_name)
+            {
+                relationship = attr(e, b"id", decoder)?;
+            }
+        }
+        Ok(())
+    })?;
+    let Some(relationship) = relationship else {
+        return Ok(None);
+    };
+
+    let mut target = None;
+    for_each_event(archive, "xl/_rels/workbook.xml.rels", |event, decoder| {
+        if let Event::Start(e) | Event::Empty(e) = event {
+            if e.local_name().as_ref() == b"Relationship"
+                && attr(e, b"Id", decoder)?.as_ref() == Some(&relationship)
+            {
+                target = attr(e, b"Target", decoder)?;
+            }
+        }
+        Ok(())
+    })?;
+    // 目标为相对 xl 目录的路径,或以 / 开头的绝对路径
+    Ok(target.map(|t| match t.strip_prefix('/') {
+        Some(absolute) => absolute.to_string(),
+        None => format!("xl/{}", t),
+    }))
+}
+
+/// 逐个读取压缩包中 XML 文件的事件,文件不存在时不做任何处理
+fn for_each_event(
+    archive: &mut Archive,
+    name: &str,
+    mut f: impl FnMut(&Event, Decoder) -> Result<(), String>,
+) -> Result<(), String> {
+    let entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(ZipError::FileNotFound) => return Ok(()),
+        Err(e) => return Err(format!("读取 {} 失败: {}", name, e)),
+    };
+    let mut reader = Reader::from_reader(BufReader::new(entry));
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Eof) => return Ok(()),
+            Ok(event) => f(&event, reader.decoder())?,
+            Err(e) => return Err(format!("解析 {} 失败: {}", name, e)),
+        }
+        buf.clear();
+    }
+}
+
+/// 按本地名称(忽略命名空间前缀)读取属性值
+fn attr(element: &BytesStart, name: &[u8], decoder: Decoder) -> Result<Option<String>, String> {
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|e| e.to_string())?;
+        if attribute.key.local_name().as_ref() == name {
+            let value = attribute
+                .decode_and_unescape_value(decoder)
+                .map_err(|e| e.to_string())?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// 解析单元格引用,如 "H2" 为 (2, 7)
+fn cell_position(reference: &str) -> Option<(usize, usize)> {
+    let split = reference.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = reference.split_at(split);
+    let col = letters.bytes().try_fold(0usize, |col, b| {
+        b.is_ascii_uppercase()
+            .then(|| col * 26 + usize::from(b - b'A') + 1)
+    })?;
+    Some((digits.parse().ok()?, col.checked_sub(1)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_xlsxwriter::{Format, Workbook};
+
+    #[test]
+    fn finds_cells_with_builtin_and_custom_percent_formats() {
+        let path = std::env::temp_dir().join(format!("numfmt-{}.xlsx", std::process::id()));
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet().set_name("数据").unwrap();
+        worksheet.write_string(0, 0, "资产负债率(%)").unwrap();
+        worksheet
+            .write_number_with_format(1, 0, 0.35, &Format::new().set_num_format("0%"))
+            .unwrap();
+        worksheet
+            .write_number_with_format(2, 0, 0.4, &Format::new().set_num_format("0.0%"))
+            .unwrap();
+        worksheet
+            .write_number_with_format(3, 0, 45.0, &Format::new().set_num_format("0.00"))
+            .unwrap();
+        worksheet
+            .write_number_with_format(4, 0, 45.0, &Format::new().set_num_format("0\"%\""))
+            .unwrap();
+        worksheet
+            .write_number_with_format(1, 1, 0.2, &Format::new().set_num_format("0%"))
+            .unwrap();
+        workbook.save(&path).unwrap();
+
+        let cells = percent_cells(path.to_str().unwrap(), "数据", &[0]).unwrap();
+        let missing = percent_cells(path.to_str().unwrap(), "其他", &[0]).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(cells, HashSet::from([(2, 0), (3, 0)]));
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn parses_cell_references() {
+        assert_eq!(cell_position("A1"), Some((1, 0)));
+        assert_eq!(cell_position("AB12"), Some((12, 27)));
+        assert_eq!(cell_position("12"), None);
+    }
+}
@@ -0,0 +1,550 @@
+use crate::excel::cell::CellValue;
+use crate::excel::consistency::{check_consistency, consistency_rules};
+use crate::excel::reader::scan_file;
+use crate::excel::types::{CalibrationBucket, CompanyData, PdCalibration, Severity};
+use crate::excel::validate::Validator;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{LazyLock, PoisonError, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 违约标志列的标准列名,别名与用户自定义映射按 mapping.rs 解析
+const DEFAULT_FLAG_COLUMN: &str = "是否违约";
+const FEATURE_NAMES: [&str; 9] = [
+    "净利润率",
+    "资产负债率(%)",
+    "ln(1+资产总额)",
+    "研发投入占比(%)",
+    "ln(1+专利数量)",
+    "上游核心企业数量",
+    "下游客户数量",
+    "历史逾期次数",
+    "法律诉讼次数",
+];
+const RIDGE: f64 = 1e-2;
+const MAX_ITERATIONS: usize = 100;
+const CALIBRATION_BUCKETS: usize = 10;
+/// 每类样本中每 5 条留出 1 条,用于评估校准情况
+const HOLDOUT_EVERY: usize = 5;
+
+static PD_MODEL: LazyLock<RwLock<PdState>> = LazyLock::new(|| {
+    RwLock::new(PdState {
+        path: None,
+        model: None,
+    })
+});
+
+struct PdState {
+    path: Option<PathBuf>,
+    model: Option<PdModel>,
+}
+
+/// 逻辑回归违约概率模型,特征经标准化后参与计算
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdModel {
+    pub features: Vec<String>,
+    pub means: Vec<f64>,
+    pub stds: Vec<f64>,
+    pub intercept: f64,
+    pub coefficients: Vec<f64>,
+    pub calibration: PdCalibration,
+}
+
+fn features(company: &CompanyData) -> [f64; 9] {
+    let profit_margin = if company.revenue > 0.0 {
+        (company.net_profit / company.revenue).clamp(-1.0, 1.0)
+    } else {
+        0.0
+    };
+    [
+        profit_margin,
+        company.debt_to_asset_ratio,
+        company.total_assets.max(0.0).ln_1p(),
+        company.r_and_d_ratio,
+        (company.patent_count.max(0) as f64).ln_1p(),
+        company.upstream_core_companies as f64,
+        company.downstream_customers as f64,
+        company.overdue_count as f64,
+        company.legal_disputes_count as f64,
+    ]
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+impl PdModel {
+    /// 检查模型参数与当前特征一致,避免过期或手工修改的模型文件导致评分出错
+    fn validate(&self) -> Result<(), String> {
+        let k = FEATURE_NAMES.len();
+        if self.features != FEATURE_NAMES {
+            return Err(format!(
+                "模型特征与当前版本不一致,需要 {} 个特征: {}",
+                k,
+                FEATURE_NAMES.join("、")
+            ));
+        }
+        for (name, values) in [
+            ("means", &self.means),
+            ("stds", &self.stds),
+            ("coefficients", &self.coefficients),
+        ] {
+            if values.len() != k {
+                return Err(format!("{} 应有 {} 个值,实际为 {}", name, k, values.len()));
+            }
+            if values.iter().any(|v| !v.is_finite()) {
+                return Err(format!("{} 中存在无效数值", name));
+            }
+        }
+        if !self.intercept.is_finite() {
+            return Err("截距无效".into());
+        }
+        if let Some(i) = self.stds.iter().position(|s| *s <= 0.0) {
+            return Err(format!("特征 {} 的标准差必须大于 0", FEATURE_NAMES[i]));
+        }
+        Ok(())
+    }
+
+    pub fn predict(&self, company: &CompanyData) -> f64 {
+        let x = features(company);
+        let z = self.intercept
+            + x.iter()
+                .enumerate()
+                .map(|(i, v)| self.coefficients[i] * (v - self.means[i]) / self.stds[i])
+                .sum::<f64>();
+        sigmoid(z)
+    }
+}
+
+/// 解析违约标志
+fn parse_default_flag(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "是" | "违约" | "true" | "y" | "yes" => Some(true),
+        "0" | "否" | "正常" | "false" | "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// 读取带违约标志的历史样本,与导入共用读取和校验流程,支持的文件格式相同。
+/// 存在未通过校验的行时拒绝整个文件,避免无法解析的单元格按 0 参与训练或回测;
+/// 与导入一样按当前一致性规则修正数据。source 为数据来源,用于匹配用户自定义的列映射
+pub fn load_labeled_samples(
+    path: &str,
+    source: Option<&str>,
+) -> Result<Vec<(CompanyData, bool)>, String> {
+    let mut validator = Validator::new();
+    let rules = consistency_rules();
+    let mut samples = Vec::new();
+    scan_file(
+        path,
+        source,
+        &mut validator,
+        &mut |location, row_data, mut company| {
+            let row = format!("{} 第 {} 行", location.sheet_name, location.row);
+            let flag = row_data
+                .get(DEFAULT_FLAG_COLUMN)
+                .map(CellValue::as_text)
+                .ok_or_else(|| format!("{} 缺少违约标志列(是否违约)", row))?;
+            let defaulted = parse_default_flag(&flag)
+                .ok_or_else(|| format!("{} 违约标志无法识别: {}", row, flag))?;
+            check_consistency(&mut company, &rules);
+            samples.push((company, defaulted));
+            Ok(())
+        },
+    )?;
+
+    let mut errors = validator
+        .diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error);
+    if let Some(first) = errors.next() {
+        return Err(format!(
+            "样本文件有 {} 处错误,例如 {} 第 {} 行: {}",
+            errors.count() + 1,
+            first.sheet_name,
+            first.row,
+            first.message
+        ));
+    }
+    Ok(samples)
+}
+
+/// 高斯消元求解线性方程组
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in col + 1..n {
+            let factor = a[row][col] / a[col][col];
+            let (upper, lower) = a.split_at_mut(row);
+            for (target, pivot) in lower[0][col..].iter_mut().zip(&upper[col][col..]) {
+                *target -= factor * pivot;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+/// 按预测违约概率分组统计校准情况
+pub fn calibrate(predictions: &[(f64, bool)]) -> PdCalibration {
+    let n = predictions.len();
+    let defaults = predictions.iter().filter(|(_, d)| *d).count();
+    let eps = 1e-12;
+
+    let brier_score = predictions
+        .iter()
+        .map(|(p, d)| (p - if *d { 1.0 } else { 0.0 }).powi(2))
+        .sum::<f64>()
+        / n.max(1) as f64;
+    let log_loss = -predictions
+        .iter()
+        .map(|(p, d)| {
+            let p = p.clamp(eps, 1.0 - eps);
+            if *d {
+                p.ln()
+            } else {
+                (1.0 - p).ln()
+            }
+        })
+        .sum::<f64>()
+        / n.max(1) as f64;
+
+    let mut sorted = predictions.to_vec();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let bucket_count = CALIBRATION_BUCKETS.min(n).max(1);
+    let mut buckets = Vec::new();
+    let mut hosmer_lemeshow = 0.0;
+    for g in 0..bucket_count {
+        let slice = &sorted[g * n / bucket_count..(g + 1) * n / bucket_count];
+        if slice.is_empty() {
+            continue;
+        }
+        let count = slice.len();
+        let observed = slice.iter().filter(|(_, d)| *d).count();
+        let mean_pd = slice.iter().map(|(p, _)| p).sum::<f64>() / count as f64;
+        let expected = mean_pd * count as f64;
+        let variance = expected * (1.0 - mean_pd);
+        if variance > eps {
+            hosmer_lemeshow += (observed as f64 - expected).powi(2) / variance;
+        }
+        buckets.push(CalibrationBucket {
+            count,
+            defaults: observed,
+            mean_pd,
+            observed_default_rate: observed as f64 / count as f64,
+            min_pd: slice[0].0,
+            max_pd: slice[count - 1].0,
+        });
+    }
+
+    PdCalibration {
+        sample_count: n,
+        default_count: defaults,
+        observed_default_rate: defaults as f64 / n.max(1) as f64,
+        mean_pd: predictions.iter().map(|(p, _)| p).sum::<f64>() / n.max(1) as f64,
+        brier_score,
+        log_loss,
+        hosmer_lemeshow,
+        hosmer_lemeshow_dof: buckets.len().saturating_sub(2),
+        buckets,
+        held_out: false,
+        trained_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default(),
+    }
+}
+
+/// 带违约标志的样本
+type Sample<'a> = (&'a CompanyData, bool);
+
+/// 按违约与否分层,每类中每 HOLDOUT_EVERY 条取一条作为留出样本,其余用于拟合
+fn split_holdout(samples: &[(CompanyData, bool)]) -> (Vec<Sample<'_>>, Vec<Sample<'_>>) {
+    let (mut train, mut holdout) = (Vec::new(), Vec::new());
+    let mut seen = [0usize; 2];
+    for (company, defaulted) in samples {
+        let class = &mut seen[usize::from(*defaulted)];
+        *class += 1;
+        if *class % HOLDOUT_EVERY == 0 {
+            holdout.push((company, *defaulted));
+        } else {
+            train.push((company, *defaulted));
+        }
+    }
+    (train, holdout)
+}
+
+/// 以牛顿法(带 L2 正则)拟合逻辑回归模型。
+/// 违约与未违约样本各不少于 HOLDOUT_EVERY 条时,以留出样本评估校准情况,模型只用其余样本拟合;
+/// 否则使用全部样本拟合,校准统计为样本内结果
+pub fn fit_model(samples: &[(CompanyData, bool)]) -> Result<PdModel, String> {
+    let k = FEATURE_NAMES.len();
+    let defaults = samples.iter().filter(|(_, d)| *d).count();
+    if defaults == 0 || defaults == samples.len() {
+        return Err("训练样本需同时包含违约与未违约企业".into());
+    }
+    if samples.len() <= k {
+        return Err(format!("训练样本过少,至少需要 {} 条", k + 1));
+    }
+
+    let (train, holdout) = split_holdout(samples);
+    let held_out =
+        defaults >= HOLDOUT_EVERY && samples.len() - defaults >= HOLDOUT_EVERY && train.len() > k;
+    let all: Vec<Sample> = samples.iter().map(|(c, d)| (c, *d)).collect();
+    let (fitted, evaluated) = if held_out {
+        (train, holdout)
+    } else {
+        (all.clone(), all)
+    };
+
+    let mut model = fit_logistic(&fitted)?;
+    let predictions: Vec<(f64, bool)> = evaluated
+        .iter()
+        .map(|(c, d)| (model.predict(c), *d))
+        .collect();
+    model.calibration = calibrate(&predictions);
+    model.calibration.held_out = held_out;
+    model.validate()?;
+
+    Ok(model)
+}
+
+fn fit_logistic(samples: &[Sample]) -> Result<PdModel, String> {
+    let k = FEATURE_NAMES.len();
+    let raw: Vec<[f64; 9]> = samples.iter().map(|(c, _)| features(c)).collect();
+    let n = raw.len() as f64;
+    let means: Vec<f64> = (0..k)
+        .map(|j| raw.iter().map(|x| x[j]).sum::<f64>() / n)
+        .collect();
+    let stds: Vec<f64> = (0..k)
+        .map(|j| {
+            let var = raw.iter().map(|x| (x[j] - means[j]).powi(2)).sum::<f64>() / n;
+            // 常量特征不参与区分,避免除零
+            if var > 1e-12 {
+                var.sqrt()
+            } else {
+                1.0
+            }
+        })
+        .collect();
+    // 设计矩阵首列为截距
+    let design: Vec<Vec<f64>> = raw
+        .iter()
+        .map(|x| {
+            std::iter::once(1.0)
+                .chain((0..k).map(|j| (x[j] - means[j]) / stds[j]))
+                .collect()
+        })
+        .collect();
+    let labels: Vec<f64> = samples
+        .iter()
+        .map(|(_, d)| if *d { 1.0 } else { 0.0 })
+        .collect();
+
+    let mut beta = vec![0.0; k + 1];
+    for _ in 0..MAX_ITERATIONS {
+        let mut gradient = vec![0.0; k + 1];
+        let mut hessian = vec![vec![0.0; k + 1]; k + 1];
+        for (x, y) in design.iter().zip(&labels) {
+            let p = sigmoid(x.iter().zip(&beta).map(|(a, b)| a * b).sum());
+            let w = p * (1.0 - p);
+            for i in 0..=k {
+                gradient[i] += (y - p) * x[i];
+                for j in 0..=k {
+                    hessian[i][j] += w * x[i] * x[j];
+                }
+            }
+        }
+        // 截距不做正则
+        for i in 1..=k {
+            gradient[i] -= RIDGE * beta[i];
+            hessian[i][i] += RIDGE;
+        }
+
+        let step = solve(hessian, gradient).ok_or("模型拟合失败,特征矩阵奇异")?;
+        let mut max_step: f64 = 0.0;
+        for (b, s) in beta.iter_mut().zip(&step) {
+            *b += s;
+            max_step = max_step.max(s.abs());
+        }
+        if max_step < 1e-8 {
+            break;
+        }
+    }
+
+    Ok(PdModel {
+        features: FEATURE_NAMES.iter().map(|s| s.to_string()).collect(),
+        means,
+        stds,
+        intercept: beta[0],
+        coefficients: beta[1..].to_vec(),
+        calibration: calibrate(&[]),
+    })
+}
+
+/// 启动时加载已保存的违约概率模型
+pub fn init_pd_model(path: PathBuf) -> Result<(), String> {
+    let mut state = PD_MODEL.write().unwrap_or_else(PoisonError::into_inner);
+    if let Ok(content) = fs::read_to_string(&path) {
+        let model = serde_json::from_str::<PdModel>(&content)
+            .map_err(|e| e.to_string())
+            .and_then(|model| model.validate().map(|_| model));
+        match model {
+            Ok(model) => state.model = Some(model),
+            Err(e) => eprintln!("加载违约概率模型失败: {}", e),
+        }
+    }
+    state.path = Some(path);
+    Ok(())
+}
+
+/// 使用当前模型预测违约概率,尚未拟合模型时返回 None
+pub fn predict_pd(company: &CompanyData) -> Option<f64> {
+    let state = PD_MODEL.read().unwrap_or_else(PoisonError::into_inner);
+    state.model.as_ref().map(|m| m.predict(company))
+}
+
+pub fn current_model() -> Option<PdModel> {
+    let state = PD_MODEL.read().unwrap_or_else(PoisonError::into_inner);
+    state.model.clone()
+}
+
+/// 拟合并启用新模型,同时保存到配置目录
+pub fn train_pd_model(path: &str, source: Option<&str>) -> Result<PdCalibration, String> {
+    let samples = load_labeled_samples(path, source)?;
+    let model = fit_model(&samples)?;
+    let calibration = model.calibration.clone();
+
+    let mut state = PD_MODEL.write().unwrap_or_else(PoisonError::into_inner);
+    if let Some(target) = &state.path {
+        let content = serde_json::to_string_pretty(&model).map_err(|e| e.to_string())?;
+        fs::write(target, content).map_err(|e| format!("保存违约概率模型失败: {}", e))?;
+    }
+    state.model = Some(model);
+
+    Ok(calibration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn company(debt_to_asset_ratio: f64, overdue_count: i32) -> CompanyData {
+        serde_json::from_value(serde_json::json!({
+            "企业ID": "C1",
+            "企业名称": "甲",
+            "行业": "人工智能",
+            "营业收入(万元)": 1000.0,
+            "净利润(万元)": 50.0,
+            "资产总额(万元)": 20000.0,
+            "负债总额(万元)": 200.0 * debt_to_asset_ratio,
+            "资产负债率(%)": debt_to_asset_ratio,
+            "研发投入占比(%)": 5.0,
+            "专利数量": 3,
+            "上游核心企业数量": 2,
+            "下游客户数量": 5,
+            "历史逾期次数": overdue_count,
+            "法律诉讼次数": 0
+        }))
+        .unwrap()
+    }
+
+    /// 违约概率随资产负债率和逾期次数上升的模拟样本
+    fn samples(n: usize) -> Vec<(CompanyData, bool)> {
+        let mut seed: u64 = 7;
+        let mut uniform = || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as f64 / (1u64 << 31) as f64
+        };
+        (0..n)
+            .map(|_| {
+                let debt = uniform() * 100.0;
+                let overdue = (uniform() * 6.0) as i32;
+                let z = -4.0 + 0.04 * debt + 0.6 * overdue as f64;
+                (company(debt, overdue), uniform() < sigmoid(z))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fits_risk_directions_and_calibrates_on_held_out_samples() {
+        let samples = samples(500);
+        let model = fit_model(&samples).unwrap();
+
+        let debt = FEATURE_NAMES
+            .iter()
+            .position(|f| *f == "资产负债率(%)")
+            .unwrap();
+        let overdue = FEATURE_NAMES
+            .iter()
+            .position(|f| *f == "历史逾期次数")
+            .unwrap();
+        assert!(model.coefficients[debt] > 0.0);
+        assert!(model.coefficients[overdue] > 0.0);
+        assert!(model.predict(&company(90.0, 4)) > model.predict(&company(10.0, 0)));
+
+        let (_, holdout) = split_holdout(&samples);
+        let calibration = &model.calibration;
+        assert!(calibration.held_out);
+        assert_eq!(calibration.sample_count, holdout.len());
+        assert_eq!(
+            calibration.buckets.iter().map(|b| b.count).sum::<usize>(),
+            holdout.len()
+        );
+    }
+
+    #[test]
+    fn small_samples_report_in_sample_calibration() {
+        let samples: Vec<(CompanyData, bool)> = (0..12)
+            .map(|i| (company(10.0 * i as f64, i % 3), i % 4 == 0))
+            .collect();
+        let model = fit_model(&samples).unwrap();
+        assert!(!model.calibration.held_out);
+        assert_eq!(model.calibration.sample_count, samples.len());
+    }
+
+    #[test]
+    fn holdout_keeps_both_classes() {
+        let samples = samples(200);
+        let (train, holdout) = split_holdout(&samples);
+        assert_eq!(train.len() + holdout.len(), samples.len());
+        for class in [true, false] {
+            let total = samples.iter().filter(|(_, d)| *d == class).count();
+            let held = holdout.iter().filter(|(_, d)| *d == class).count();
+            assert_eq!(held, total / HOLDOUT_EVERY);
+        }
+    }
+
+    #[test]
+    fn default_flag_column_resolves_through_header_aliases() {
+        let path = std::env::temp_dir().join(format!("pd-samples-{}.csv", std::process::id()));
+        fs::write(
+            &path,
+            "企业ID,企业名称,行业,营业收入(万元),净利润(万元),资产总额(万元),负债总额(万元),\
+             资产负债率(%),研发投入占比(%),专利数量,上游核心企业数量,下游客户数量,\
+             历史逾期次数,法律诉讼次数,Default Flag\n\
+             C1,甲,人工智能,1000,50,2000,800,40,5,3,2,5,0,0,是\n\
+             C2,乙,人工智能,1000,50,2000,800,40,5,3,2,5,0,0,0\n",
+        )
+        .unwrap();
+        let samples = load_labeled_samples(path.to_str().unwrap(), None);
+        fs::remove_file(&path).unwrap();
+
+        let flags: Vec<bool> = samples.unwrap().iter().map(|(_, d)| *d).collect();
+        assert_eq!(flags, [true, false]);
+    }
+}
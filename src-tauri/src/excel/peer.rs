@@ -0,0 +1,246 @@
+use crate::excel::types::{CompanyWithScore, ExcelResult, PeerPercentile, PeerStats};
+use std::collections::HashMap;
+
+/// 参与同业对标的指标
+struct Metric {
+    key: &'static str,
+    name: &'static str,
+    get: fn(&CompanyWithScore) -> f64,
+}
+
+const METRICS: &[Metric] = &[
+    Metric {
+        key: "revenue",
+        name: "营业收入(万元)",
+        get: |c| c.company_data.revenue,
+    },
+    Metric {
+        key: "profit_margin",
+        name: "净利润率",
+        get: |c| {
+            if c.company_data.revenue > 0.0 {
+                c.company_data.net_profit / c.company_data.revenue
+            } else {
+                0.0
+            }
+        },
+    },
+    Metric {
+        key: "debt_to_asset_ratio",
+        name: "资产负债率(%)",
+        get: |c| c.company_data.debt_to_asset_ratio,
+    },
+    Metric {
+        key: "r_and_d_ratio",
+        name: "研发投入占比(%)",
+        get: |c| c.company_data.r_and_d_ratio,
+    },
+    Metric {
+        key: "patent_count",
+        name: "专利数量",
+        get: |c| c.company_data.patent_count as f64,
+    },
+    Metric {
+        key: "credit_score",
+        name: "信用评分",
+        get: |c| c.credit_score,
+    },
+];
+
+/// 行业内某项指标的全部取值(含自身),已排序,
+/// 便于在 O(log n) 内求出排除自身后的同业统计量
+struct SortedValues(Vec<f64>);
+
+impl SortedValues {
+    fn new(mut values: Vec<f64>) -> Self {
+        values.sort_by(f64::total_cmp);
+        SortedValues(values)
+    }
+
+    /// 百分位排名:同业中低于该值的比例,相等者记一半
+    fn percentile_rank(&self, value: f64) -> f64 {
+        let below = self.0.partition_point(|v| *v < value);
+        let equal = (self.0.partition_point(|v| *v <= value) - below).saturating_sub(1);
+        (below as f64 + equal as f64 / 2.0) / (self.0.len() - 1) as f64 * 100.0
+    }
+
+    /// 去掉一个等于自身取值的元素后的中位数
+    fn median(&self, value: f64) -> f64 {
+        let skip = self.0.partition_point(|v| *v < value);
+        let at = |i: usize| if i < skip { self.0[i] } else { self.0[i + 1] };
+        let len = self.0.len() - 1;
+        let mid = len / 2;
+        if len.is_multiple_of(2) {
+            (at(mid - 1) + at(mid)) / 2.0
+        } else {
+            at(mid)
+        }
+    }
+}
+
+/// 按行业对同一批次上传的企业做同业对标,结果写入各 ExcelResult
+pub fn benchmark_peers(results: &mut [ExcelResult]) {
+    // 每个行业按指标收集取值,排序一次后供所有成员共用
+    let mut groups: HashMap<&str, Vec<Vec<f64>>> = HashMap::new();
+    for company in results.iter().flat_map(|r| &r.companies) {
+        let columns = groups
+            .entry(company.company_data.industry.as_str())
+            .or_insert_with(|| vec![Vec::new(); METRICS.len()]);
+        for (metric, column) in METRICS.iter().zip(columns) {
+            column.push((metric.get)(company));
+        }
+    }
+    let groups: HashMap<&str, Vec<SortedValues>> = groups
+        .into_iter()
+        .map(|(industry, columns)| {
+            (
+                industry,
+                columns.into_iter().map(SortedValues::new).collect(),
+            )
+        })
+        .collect();
+
+    let stats: Vec<Vec<PeerStats>> = results
+        .iter()
+        .map(|result| {
+            result
+                .companies
+                .iter()
+                .map(|company| peer_stats(company, &groups[company.company_data.industry.as_str()]))
+                .collect()
+        })
+        .collect();
+
+    for (result, peer_stats) in results.iter_mut().zip(stats) {
+        result.peer_stats = peer_stats;
+    }
+}
+
+fn peer_stats(company: &CompanyWithScore, columns: &[SortedValues]) -> PeerStats {
+    // 行业成员中除自身以外的企业数
+    let peer_count = columns.first().map_or(0, |c| c.0.len() - 1);
+    let percentiles = if peer_count == 0 {
+        Vec::new()
+    } else {
+        METRICS
+            .iter()
+            .zip(columns)
+            .map(|(metric, column)| {
+                let value = (metric.get)(company);
+                PeerPercentile {
+                    metric: metric.key.to_string(),
+                    metric_name: metric.name.to_string(),
+                    value,
+                    percentile: column.percentile_rank(value),
+                    peer_median: column.median(value),
+                }
+            })
+            .collect()
+    };
+
+    PeerStats {
+        company_id: company.company_data.company_id.clone(),
+        industry: company.company_data.industry.clone(),
+        peer_count,
+        percentiles,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::excel::calc::score_company;
+    use crate::excel::scorecard::{Scorecard, DEFAULT_SCORECARD};
+    use crate::excel::types::CompanyData;
+
+    fn scored(scorecard: &Scorecard, id: &str, industry: &str, revenue: f64) -> CompanyWithScore {
+        let company: CompanyData = serde_json::from_value(serde_json::json!({
+            "企业ID": id,
+            "企业名称": id,
+            "行业": industry,
+            "营业收入(万元)": revenue,
+            "净利润(万元)": revenue / 10.0,
+            "资产总额(万元)": 3000.0,
+            "负债总额(万元)": 1200.0,
+            "资产负债率(%)": 40.0,
+            "研发投入占比(%)": 5.0,
+            "专利数量": 3,
+            "上游核心企业数量": 2,
+            "下游客户数量": 5,
+            "历史逾期次数": 0,
+            "法律诉讼次数": 0
+        }))
+        .unwrap();
+        score_company(company, scorecard)
+    }
+
+    fn result(scorecard: &Scorecard, companies: Vec<CompanyWithScore>) -> ExcelResult {
+        ExcelResult {
+            file: "a.xlsx".into(),
+            sheet_name: "Sheet1".into(),
+            total_companies: companies.len(),
+            scorecard: scorecard.id(),
+            companies,
+            peer_stats: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn ranks_against_peers_excluding_self() {
+        let values = SortedValues::new(vec![5.0, 1.0, 3.0, 3.0, 2.0]);
+        assert_eq!(values.percentile_rank(1.0), 0.0);
+        // 低于 3 的有两家,另一家与之相等记一半
+        assert_eq!(values.percentile_rank(3.0), 62.5);
+        assert_eq!(values.percentile_rank(5.0), 100.0);
+        assert_eq!(values.median(1.0), 3.0);
+        assert_eq!(values.median(5.0), 2.5);
+        assert_eq!(values.median(3.0), 2.5);
+    }
+
+    #[test]
+    fn benchmarks_industries_across_the_batch() {
+        let scorecard = Scorecard::from_json(DEFAULT_SCORECARD).unwrap();
+        let mut results = vec![
+            result(
+                &scorecard,
+                vec![
+                    scored(&scorecard, "A", "制造业", 100.0),
+                    scored(&scorecard, "B", "制造业", 200.0),
+                    scored(&scorecard, "lone", "软件", 500.0),
+                ],
+            ),
+            result(
+                &scorecard,
+                vec![
+                    scored(&scorecard, "C", "制造业", 300.0),
+                    scored(&scorecard, "D", "制造业", 300.0),
+                ],
+            ),
+        ];
+
+        benchmark_peers(&mut results);
+
+        let revenue = |stats: &PeerStats| {
+            stats
+                .percentiles
+                .iter()
+                .find(|p| p.metric == "revenue")
+                .map(|p| (p.percentile, p.peer_median))
+                .unwrap()
+        };
+        let first = &results[0].peer_stats;
+        assert_eq!(first.len(), 3);
+        assert_eq!(first[0].peer_count, 3);
+        assert_eq!(revenue(&first[0]), (0.0, 300.0));
+        let (percentile, median) = revenue(&first[1]);
+        assert!((percentile - 100.0 / 3.0).abs() < 1e-9);
+        assert_eq!(median, 300.0);
+        assert_eq!(first[2].peer_count, 0);
+        assert!(first[2].percentiles.is_empty());
+
+        let (percentile, median) = revenue(&results[1].peer_stats[0]);
+        assert!((percentile - 250.0 / 3.0).abs() < 1e-9);
+        assert_eq!(median, 200.0);
+        assert_eq!(results[1].peer_stats[1].percentiles.len(), METRICS.len());
+    }
+}
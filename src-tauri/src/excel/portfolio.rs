@@ -0,0 +1,302 @@
+use crate::excel::calc::{suggested_exposure, RATING_GRADES};
+use crate::excel::types::{
+    CompanyWithScore, ExcelResult, ExposureBucket, PortfolioCompany, PortfolioSummary,
+};
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use std::collections::HashMap;
+
+/// 风险等级由低到高
+const RISK_LEVELS: [&str; 4] = ["低", "中", "高", "极高"];
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator == 0.0 {
+        0.0
+    } else {
+        numerator / denominator
+    }
+}
+
+/// 分组累计的企业数、敞口与评分
+#[derive(Default)]
+struct Accumulator {
+    count: usize,
+    exposure: f64,
+    score_sum: f64,
+}
+
+impl Accumulator {
+    fn add(&mut self, company: &PortfolioCompany) {
+        self.count += 1;
+        self.exposure += company.exposure;
+        self.score_sum += company.credit_score;
+    }
+
+    fn bucket(&self, name: &str, total_exposure: f64) -> ExposureBucket {
+        ExposureBucket {
+            name: name.to_string(),
+            count: self.count,
+            exposure: self.exposure,
+            exposure_share: ratio(self.exposure, total_exposure),
+            average_score: ratio(self.score_sum, self.count as f64),
+        }
+    }
+}
+
+impl From<&CompanyWithScore> for PortfolioCompany {
+    fn from(c: &CompanyWithScore) -> Self {
+        Self {
+            company_id: c.company_data.company_id.clone(),
+            company_name: c.company_data.company_name.clone(),
+            industry: c.company_data.industry.clone(),
+            credit_score: c.credit_score,
+            credit_rating: c.credit_rating.clone(),
+            risk_level: c.risk_level.clone(),
+            exposure: suggested_exposure(&c.credit_limit),
+            probability_of_default: c.probability_of_default,
+        }
+    }
+}
+
+/// 汇总一次导入的组合指标,top_n 为最大敞口和最高风险名单的长度
+pub fn analyze_portfolio(
+    import_id: i64,
+    results: &[ExcelResult],
+    top_n: usize,
+) -> PortfolioSummary {
+    let companies: Vec<PortfolioCompany> = results
+        .iter()
+        .flat_map(|r| &r.companies)
+        .map(PortfolioCompany::from)
+        .collect();
+    let total_companies = companies.len();
+    let total_exposure: f64 = companies.iter().map(|c| c.exposure).sum();
+    let score_sum: f64 = companies.iter().map(|c| c.credit_score).sum();
+    let average_score = ratio(score_sum, total_companies as f64);
+    let weighted_average_score = if total_exposure > 0.0 {
+        companies
+            .iter()
+            .map(|c| c.credit_score * c.exposure)
+            .sum::<f64>()
+            / total_exposure
+    } else {
+        average_score
+    };
+
+    let mut ratings: HashMap<&str, Accumulator> = HashMap::new();
+    let mut industries: HashMap<&str, Accumulator> = HashMap::new();
+    let mut risk_levels: HashMap<&str, Accumulator> = HashMap::new();
+    for company in &companies {
+        ratings
+            .entry(&company.credit_rating)
+            .or_default()
+            .add(company);
+        industries
+            .entry(&company.industry)
+            .or_default()
+            .add(company);
+        risk_levels
+            .entry(&company.risk_level)
+            .or_default()
+            .add(company);
+    }
+
+    let rating_distribution = RATING_GRADES
+        .iter()
+        .rev()
+        .map(|grade| match ratings.get(grade) {
+            Some(acc) => acc.bucket(grade, total_exposure),
+            None => Accumulator::default().bucket(grade, total_exposure),
+        })
+        .collect();
+
+    let mut industry_exposure: Vec<ExposureBucket> = industries
+        .iter()
+        .map(|(name, acc)| acc.bucket(name, total_exposure))
+        .collect();
+    industry_exposure.sort_by(|a, b| {
+        b.exposure
+            .total_cmp(&a.exposure)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    // HHI 以百分比份额计算,单一行业时为 10000
+    let industry_hhi = industry_exposure
+        .iter()
+        .map(|b| (b.exposure_share * 100.0).powi(2))
+        .sum();
+
+    let mut risk_level_exposure: Vec<ExposureBucket> = risk_levels
+        .iter()
+        .map(|(level, acc)| acc.bucket(level, total_exposure))
+        .collect();
+    // 按风险由低到高排列,评分卡之外的风险等级排在最后
+    risk_level_exposure.sort_by_key(|b| {
+        let rank = RISK_LEVELS.iter().position(|level| *level == b.name);
+        (rank.unwrap_or(RISK_LEVELS.len()), b.name.clone())
+    });
+
+    let mut top_exposures = companies.clone();
+    top_exposures.sort_by(|a, b| {
+        b.exposure
+            .total_cmp(&a.exposure)
+            .then_with(|| b.credit_score.total_cmp(&a.credit_score))
+    });
+    top_exposures.truncate(top_n);
+
+    let mut top_risks = companies;
+    top_risks.sort_by(|a, b| {
+        a.credit_score
+            .total_cmp(&b.credit_score)
+            .then_with(|| b.exposure.total_cmp(&a.exposure))
+    });
+    top_risks.truncate(top_n);
+
+    PortfolioSummary {
+        import_id,
+        total_companies,
+        total_exposure,
+        average_score,
+        weighted_average_score,
+        rating_distribution,
+        industry_exposure,
+        risk_level_exposure,
+        industry_hhi,
+        top_exposures,
+        top_risks,
+    }
+}
+
+/// 导出组合分析报告
+pub fn write_portfolio_report(file_path: &str, summary: &PortfolioSummary) -> Result<(), String> {
+    let mut workbook = Workbook::new();
+    write_overview(workbook.add_worksheet(), summary).map_err(|e| e.to_string())?;
+    let bucket_sheets = [
+        ("评级分布", "信用评级", &summary.rating_distribution),
+        ("行业敞口", "行业", &summary.industry_exposure),
+        ("风险等级敞口", "风险等级", &summary.risk_level_exposure),
+    ];
+    for (sheet_name, label, buckets) in bucket_sheets {
+        let worksheet = workbook.add_worksheet();
+        write_buckets(worksheet, sheet_name, label, buckets).map_err(|e| e.to_string())?;
+    }
+    let company_sheets = [
+        ("最大敞口", &summary.top_exposures),
+        ("最高风险", &summary.top_risks),
+    ];
+    for (sheet_name, companies) in company_sheets {
+        let worksheet = workbook.add_worksheet();
+        write_companies(worksheet, sheet_name, companies).map_err(|e| e.to_string())?;
+    }
+    workbook.save(file_path).map_err(|e| e.to_string())
+}
+
+fn write_overview(worksheet: &mut Worksheet, summary: &PortfolioSummary) -> Result<(), XlsxError> {
+    let header_format = Format::new().set_bold().set_font_size(14.0);
+    let key_format = Format::new().set_bold();
+    let amount_format = Format::new().set_num_format("#,##0.00");
+    let score_format = Format::new().set_num_format("0.00");
+
+    worksheet.set_name("组合概览")?;
+    worksheet.set_column_width(0, 24.0)?;
+    worksheet.set_column_width(1, 20.0)?;
+    worksheet.write_string_with_format(0, 0, "组合分析报告", &header_format)?;
+
+    let top_industry = summary.industry_exposure.first();
+    let rows = [
+        ("导入记录", summary.import_id as f64, None),
+        ("企业数", summary.total_companies as f64, None),
+        (
+            "建议授信总敞口(万元)",
+            summary.total_exposure,
+            Some(&amount_format),
+        ),
+        ("平均评分", summary.average_score, Some(&score_format)),
+        (
+            "敞口加权平均评分",
+            summary.weighted_average_score,
+            Some(&score_format),
+        ),
+        ("行业集中度 HHI", summary.industry_hhi, Some(&score_format)),
+    ];
+    let mut row = 2;
+    for (key, value, format) in rows {
+        worksheet.write_string_with_format(row, 0, key, &key_format)?;
+        match format {
+            Some(format) => worksheet.write_number_with_format(row, 1, value, format)?,
+            None => worksheet.write_number(row, 1, value)?,
+        };
+        row += 1;
+    }
+    if let Some(industry) = top_industry {
+        worksheet.write_string_with_format(row, 0, "敞口最大行业", &key_format)?;
+        worksheet.write_string(row, 1, &industry.name)?;
+    }
+    Ok(())
+}
+
+fn write_buckets(
+    worksheet: &mut Worksheet,
+    sheet_name: &str,
+    label: &str,
+    buckets: &[ExposureBucket],
+) -> Result<(), XlsxError> {
+    let key_format = Format::new().set_bold();
+    let amount_format = Format::new().set_num_format("#,##0.00");
+    let percent_format = Format::new().set_num_format("0.00%");
+    let score_format = Format::new().set_num_format("0.00");
+
+    worksheet.set_name(sheet_name)?;
+    let headers = [label, "企业数", "敞口(万元)", "敞口占比", "平均评分"];
+    for (col, header) in headers.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &key_format)?;
+        worksheet.set_column_width(col as u16, 15.0)?;
+    }
+    for (i, bucket) in buckets.iter().enumerate() {
+        let row = i as u32 + 1;
+        worksheet.write_string(row, 0, &bucket.name)?;
+        worksheet.write_number(row, 1, bucket.count as f64)?;
+        worksheet.write_number_with_format(row, 2, bucket.exposure, &amount_format)?;
+        worksheet.write_number_with_format(row, 3, bucket.exposure_share, &percent_format)?;
+        worksheet.write_number_with_format(row, 4, bucket.average_score, &score_format)?;
+    }
+    Ok(())
+}
+
+fn write_companies(
+    worksheet: &mut Worksheet,
+    sheet_name: &str,
+    companies: &[PortfolioCompany],
+) -> Result<(), XlsxError> {
+    let key_format = Format::new().set_bold();
+    let amount_format = Format::new().set_num_format("#,##0.00");
+    let percent_format = Format::new().set_num_format("0.00%");
+
+    worksheet.set_name(sheet_name)?;
+    let headers = [
+        "企业ID",
+        "企业名称",
+        "行业",
+        "信用评分",
+        "信用评级",
+        "风险等级",
+        "敞口(万元)",
+        "违约概率",
+    ];
+    for (col, header) in headers.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &key_format)?;
+        worksheet.set_column_width(col as u16, 15.0)?;
+    }
+    for (i, company) in companies.iter().enumerate() {
+        let row = i as u32 + 1;
+        worksheet.write_string(row, 0, &company.company_id)?;
+        worksheet.write_string(row, 1, &company.company_name)?;
+        worksheet.write_string(row, 2, &company.industry)?;
+        worksheet.write_number(row, 3, company.credit_score)?;
+        worksheet.write_string(row, 4, &company.credit_rating)?;
+        worksheet.write_string(row, 5, &company.risk_level)?;
+        worksheet.write_number_with_format(row, 6, company.exposure, &amount_format)?;
+        if let Some(pd) = company.probability_of_default {
+            worksheet.write_number_with_format(row, 7, pd, &percent_format)?;
+        }
+    }
+    Ok(())
+}
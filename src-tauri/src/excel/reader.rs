@@ -0,0 +1,384 @@
+use crate::excel::batch::ImportTask;
+use crate::excel::calc::parse_company_from_row;
+use crate::excel::cell::{
+    looks_like_fraction, rescale_fraction, row_data, row_from_cells, CellValue, RowData,
+    PERCENT_COLUMNS,
+};
+use crate::excel::formats::{read_tables, FileFormat};
+use crate::excel::history::{fiscal_year_from_sheet, merge_fiscal_years};
+use crate::excel::mapping::{map_headers, HeaderLayout};
+use crate::excel::numfmt::percent_cells;
+use crate::excel::types::{CompanyData, Diagnostic, SourceLocation};
+use crate::excel::units::normalize_units;
+use crate::excel::validate::Validator;
+use calamine::{open_workbook_auto, Data, Reader, Sheets, Xlsx};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufReader};
+
+/// 企业数据及其来源位置
+pub type SourcedCompany = (SourceLocation, CompanyData);
+
+/// 读取并校验所有文件中的企业数据,按文件、工作表和行的原始顺序返回;
+/// 多个年度的记录合并为一家企业,归入最新年度所在的位置
+pub fn read_companies(
+    paths: &[String],
+    source: Option<&str>,
+    task: &ImportTask,
+) -> Result<(Vec<SourcedCompany>, Vec<Diagnostic>), String> {
+    // 同一个校验器贯穿所有文件,以识别跨文件重复的企业ID
+    let mut validator = Validator::new();
+    let mut entries = Vec::new();
+    for path in paths {
+        scan_file(path, source, &mut validator, &mut |location, _, company| {
+            task.check_cancelled()?;
+            entries.push((location, company));
+            task.row_read();
+            Ok(())
+        })?;
+        task.file_read();
+    }
+    Ok((merge_fiscal_years(entries), validator.diagnostics))
+}
+
+/// 文件内容的 SHA-256 十六进制摘要,用于追溯评分所用的原始文件
+pub fn file_hash(path: &str) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("无法打开文件 {}: {}", path, e))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).map_err(|e| format!("读取文件 {} 失败: {}", path, e))?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// 逐行读取单个文件中的企业数据,连同该行的单元格回调 f,不保留原始行;
+/// 未通过校验的行只记录诊断信息。
+/// 支持 Excel、CSV、JSON 与 JSON Lines,文本格式以文件名作为工作表名
+pub fn scan_file(
+    path: &str,
+    source: Option<&str>,
+    validator: &mut Validator,
+    f: &mut impl FnMut(SourceLocation, &RowData, CompanyData) -> Result<(), String>,
+) -> Result<(), String> {
+    let format = FileFormat::detect(path);
+    if format == FileFormat::Excel {
+        return scan_workbook(path, source, validator, f);
+    }
+    for table in read_tables(path, format)? {
+        let Some(mut sheet) = Sheet::open(path, &table.name, &table.headers, source, validator)
+        else {
+            continue;
+        };
+        for row in table.rows {
+            let (line, cells) = row?;
+            let row_data = row_from_cells(&sheet.layout.names, cells);
+            sheet.read(line, row_data, validator, f)?;
+        }
+        sheet.finish(validator);
+    }
+    Ok(())
+}
+
+/// 单个工作表的表头映射及年度信息
+struct Sheet<'a> {
+    path: &'a str,
+    name: &'a str,
+    layout: HeaderLayout,
+    year: Option<i32>,
+    /// 设置了百分比数字格式的单元格 (行号, 列名),仅 xlsx 可识别
+    percent_cells: HashSet<(usize, String)>,
+    /// 各百分比列已换算的单元格:(第一个所在行, 个数)
+    rescaled: BTreeMap<&'static str, (usize, usize)>,
+    /// 各百分比列未换算但取值在 0 到 1 之间的单元格:(第一个所在行, 个数)
+    fraction_like: BTreeMap<&'static str, (usize, usize)>,
+}
+
+impl<'a> Sheet<'a> {
+    /// 表头校验未通过时返回 None,整个工作表不参与评分
+    fn open(
+        path: &'a str,
+        name: &'a str,
+        raw_headers: &[String],
+        source: Option<&str>,
+        validator: &mut Validator,
+    ) -> Option<Self> {
+        let layout = map_headers(raw_headers, source);
+        if !validator.check_headers(path, name, &layout.names) {
+            return None;
+        }
+        Some(Sheet {
+            path,
+            name,
+            layout,
+            year: fiscal_year_from_sheet(name),
+            percent_cells: HashSet::new(),
+            rescaled: BTreeMap::new(),
+            fraction_like: BTreeMap::new(),
+        })
+    }
+
+    /// 读取 xlsx 中百分比列的数字格式,百分比格式的单元格按小数换算
+    fn read_percent_formats(&mut self) -> Result<(), String> {
+        let columns: Vec<usize> = (0..self.layout.names.len())
+            .filter(|&i| PERCENT_COLUMNS.contains(&self.layout.names[i].as_str()))
+            .collect();
+        self.percent_cells = percent_cells(self.path, self.name, &columns)?
+            .into_iter()
+            .map(|(row, col)| (row, self.layout.names[col].clone()))
+            .collect();
+        Ok(())
+    }
+
+    fn read(
+        &mut self,
+        line: usize,
+        mut row_data: RowData,
+        validator: &mut Validator,
+        f: &mut impl FnMut(SourceLocation, &RowData, CompanyData) -> Result<(), String>,
+    ) -> Result<(), String> {
+        if row_data.values().all(CellValue::is_blank) {
+            return Ok(());
+        }
+
+        self.rescale_percentages(line, &mut row_data);
+        let mut company = parse_company_from_row(&row_data);
+        company.fiscal_year = company.fiscal_year.or(self.year);
+        normalize_units(&mut company, &self.layout.units);
+
+        if validator.check_row((self.path, self.name, line), &row_data, &company) {
+            let location = SourceLocation {
+                file: self.path.to_string(),
+                sheet_name: self.name.to_string(),
+                row: line,
+            };
+            f(location, &row_data, company)?;
+        }
+        Ok(())
+    }
+
+    /// 列名标注为小数或单元格为百分比格式时,百分比列的取值乘以 100 换算为百分点;
+    /// 其余取值在 0 到 1 之间的单元格只计数,读完工作表后提示
+    fn rescale_percentages(&mut self, line: usize, row_data: &mut RowData) {
+        for column in PERCENT_COLUMNS {
+            let Some(value) = row_data.get_mut(column) else {
+                continue;
+            };
+            let fraction = self.layout.fractions.iter().any(|c| c == column)
+                || self.percent_cells.contains(&(line, column.to_string()));
+            let counts = if fraction {
+                if !rescale_fraction(value) {
+                    continue;
+                }
+                &mut self.rescaled
+            } else if looks_like_fraction(value) {
+                &mut self.fraction_like
+            } else {
+                continue;
+            };
+            counts.entry(column).or_insert((line, 0)).1 += 1;
+        }
+    }
+
+    /// 每个百分比列按工作表汇总一条换算或疑似小数的提示
+    fn finish(self, validator: &mut Validator) {
+        for (column, (row, count)) in self.rescaled {
+            validator.note_rescaled((self.path, self.name, row), column, count);
+        }
+        for (column, (row, count)) in self.fraction_like {
+            validator.note_fraction_like((self.path, self.name, row), column, count);
+        }
+    }
+}
+
+fn scan_workbook(
+    path: &str,
+    source: Option<&str>,
+    validator: &mut Validator,
+    f: &mut impl FnMut(SourceLocation, &RowData, CompanyData) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut workbook =
+        open_workbook_auto(path).map_err(|e| format!("无法打开文件 {}: {}", path, e))?;
+
+    for sheet_name in workbook.sheet_names().to_owned() {
+        // xlsx 按单元格流式读取,其余格式整表读取
+        if let Sheets::Xlsx(xlsx) = &mut workbook {
+            scan_xlsx_sheet(xlsx, path, &sheet_name, source, validator, f)?;
+            continue;
+        }
+
+        let range = workbook
+            .worksheet_range(&sheet_name)
+            .map_err(|e| format!("读取工作表 {} 失败: {}", sheet_name, e))?;
+        let first_row = range.start().map_or(0, |(row, _)| row as usize);
+        let mut rows = range.rows();
+        let Some(header_row) = rows.next() else {
+            continue;
+        };
+        let raw: Vec<String> = header_row.iter().map(|c| c.to_string()).collect();
+        let Some(mut sheet) = Sheet::open(path, &sheet_name, &raw, source, validator) else {
+            continue;
+        };
+
+        for (i, row) in rows.enumerate() {
+            let row_data = row_data(&sheet.layout.names, row);
+            // 行号从 1 开始,表头所在行之后为数据
+            sheet.read(first_row + i + 2, row_data, validator, f)?;
+        }
+        sheet.finish(validator);
+    }
+    Ok(())
+}
+
+type XlsxFile = Xlsx<BufReader<File>>;
+
+fn scan_xlsx_sheet(
+    xlsx: &mut XlsxFile,
+    path: &str,
+    sheet_name: &str,
+    source: Option<&str>,
+    validator: &mut Validator,
+    f: &mut impl FnMut(SourceLocation, &RowData, CompanyData) -> Result<(), String>,
+) -> Result<(), String> {
+    let header = xlsx_rows(xlsx, sheet_name)?.next().transpose()?;
+    let Some((_, header_row)) = header else {
+        return Ok(());
+    };
+    let raw: Vec<String> = header_row.iter().map(|c| c.to_string()).collect();
+    let Some(mut sheet) = Sheet::open(path, sheet_name, &raw, source, validator) else {
+        return Ok(());
+    };
+    sheet.read_percent_formats()?;
+
+    for row in xlsx_rows(xlsx, sheet_name)?.skip(1) {
+        let (line, row) = row?;
+        let row_data = row_data(&sheet.layout.names, &row);
+        sheet.read(line, row_data, validator, f)?;
+    }
+    sheet.finish(validator);
+    Ok(())
+}
+
+/// 按行聚合 xlsx 单元格,返回 (行号, 单元格),行号从 1 开始,空行不返回
+pub fn xlsx_rows<'a>(
+    xlsx: &'a mut XlsxFile,
+    sheet_name: &str,
+) -> Result<impl Iterator<Item = Result<(usize, Vec<Data>), String>> + 'a, String> {
+    let mut cells = xlsx
+        .worksheet_cells_reader(sheet_name)
+        .map_err(|e| format!("读取工作表 {} 失败: {}", sheet_name, e))?;
+    // 读到末尾后不能再调用 next_cell,否则会报 XML 不完整
+    let mut finished = false;
+    let mut next_cell = move || -> Result<Option<(u32, u32, Data)>, String> {
+        if finished {
+            return Ok(None);
+        }
+        let cell = cells.next_cell().map_err(|e| e.to_string())?;
+        finished = cell.is_none();
+        Ok(cell.map(|c| {
+            let (row, col) = c.get_position();
+            (row, col, Data::from(c.get_value().clone()))
+        }))
+    };
+
+    let mut pending = None;
+    Ok(std::iter::from_fn(move || {
+        let (row, col, value) = match pending.take() {
+            Some(cell) => cell,
+            None => match next_cell() {
+                Ok(Some(cell)) => cell,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            },
+        };
+        let mut values = Vec::new();
+        put(&mut values, col, value);
+        loop {
+            match next_cell() {
+                Ok(Some((r, c, v))) if r == row => put(&mut values, c, v),
+                Ok(Some(cell)) => {
+                    pending = Some(cell);
+                    break;
+                }
+                Ok(None) => break,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        Some(Ok((row as usize + 1, values)))
+    }))
+}
+
+fn put(values: &mut Vec<Data>, col: u32, value: Data) {
+    let col = col as usize;
+    if values.len() <= col {
+        values.resize(col + 1, Data::Empty);
+    }
+    values[col] = value;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_xlsxwriter::{Format, Workbook};
+
+    #[test]
+    fn percent_formatted_cells_are_read_as_percentage_points() {
+        let path = std::env::temp_dir().join(format!("reader-{}.xlsx", std::process::id()));
+        let headers = [
+            "企业ID",
+            "企业名称",
+            "行业",
+            "营业收入(万元)",
+            "净利润(万元)",
+            "资产总额(万元)",
+            "负债总额(万元)",
+            "资产负债率(%)",
+            "研发投入占比(%)",
+            "专利数量",
+            "上游核心企业数量",
+            "下游客户数量",
+            "历史逾期次数",
+            "法律诉讼次数",
+        ];
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet().set_name("2024").unwrap();
+        for (col, header) in headers.iter().enumerate() {
+            worksheet.write_string(0, col as u16, *header).unwrap();
+        }
+        for (col, text) in ["C001", "测试企业", "制造业"].iter().enumerate() {
+            worksheet.write_string(1, col as u16, *text).unwrap();
+        }
+        for (col, x) in [1000.0, 80.0, 2000.0, 900.0].iter().enumerate() {
+            worksheet.write_number(1, col as u16 + 3, *x).unwrap();
+        }
+        // 资产负债率以百分比格式显示为 45%,研发投入占比直接填写 0.05
+        let percent = Format::new().set_num_format("0%");
+        worksheet
+            .write_number_with_format(1, 7, 0.45, &percent)
+            .unwrap();
+        worksheet.write_number(1, 8, 0.05).unwrap();
+        for col in 9..14 {
+            worksheet.write_number(1, col, 1.0).unwrap();
+        }
+        workbook.save(&path).unwrap();
+
+        let file = path.to_str().unwrap().to_string();
+        let result = read_companies(&[file], None, &ImportTask::silent());
+        std::fs::remove_file(&path).unwrap();
+        let (companies, diagnostics) = result.unwrap();
+
+        let company = &companies[0].1;
+        assert_eq!(company.debt_to_asset_ratio, 45.0);
+        assert_eq!(company.r_and_d_ratio, 0.05);
+        let warning = |column: &str| {
+            diagnostics
+                .iter()
+                .find(|d| d.column.as_deref() == Some(column))
+                .map(|d| (d.row, d.message.contains("换算为百分点")))
+        };
+        assert_eq!(warning("资产负债率(%)"), Some((2, true)));
+        assert_eq!(warning("研发投入占比(%)"), Some((2, false)));
+    }
+}
@@ -0,0 +1,1007 @@
+use crate::excel::distress::{altman_z, ohlson_o, zmijewski};
+use crate::excel::history::{debt_ratio_change, margin_trend, revenue_cagr, revenue_volatility};
+use crate::excel::types::{
+    CompanyData, FactorContribution, FactorValue, ReasonCode, ScoreDetails, ScorecardId,
+};
+use credit::ScorecardRef;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+
+/// 内置默认评分卡,与规则文件格式一致
+pub const DEFAULT_SCORECARD: &str = include_str!("../../scorecards/default.json");
+const ACTIVE_FILE: &str = "active";
+
+static REGISTRY: LazyLock<RwLock<ScorecardRegistry>> =
+    LazyLock::new(|| RwLock::new(ScorecardRegistry::new()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scorecard {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_min_score")]
+    pub min_score: f64,
+    #[serde(default = "default_max_score")]
+    pub max_score: f64,
+    pub categories: Vec<Category>,
+    /// 规则内容的 SHA-256,加载时计算
+    #[serde(skip)]
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Category {
+    pub key: CategoryKey,
+    pub name: String,
+    /// 归一化满分,为空时按原始分展示
+    #[serde(default)]
+    pub max_points: Option<f64>,
+    #[serde(default)]
+    pub base_points: f64,
+    #[serde(default)]
+    pub floor: Option<f64>,
+    #[serde(default)]
+    pub cap: Option<f64>,
+    pub factors: Vec<Factor>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CategoryKey {
+    Financial,
+    Innovation,
+    SupplyChain,
+    Risk,
+    IndustryAdjustment,
+    Trend,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Factor {
+    pub key: String,
+    pub name: String,
+    /// 理由码,为空时使用因子 key
+    #[serde(default)]
+    pub code: Option<String>,
+    /// 扣分理由模板,支持 {name}、{value}、{band} 占位符
+    #[serde(default)]
+    pub reason: Option<String>,
+    pub input: FactorInput,
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    #[serde(flatten)]
+    pub rule: FactorRule,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FactorInput {
+    Revenue,
+    NetProfit,
+    ProfitMargin,
+    TotalAssets,
+    TotalLiabilities,
+    DebtToAssetRatio,
+    RAndDRatio,
+    PatentCount,
+    UpstreamCoreCompanies,
+    DownstreamCustomers,
+    OverdueCount,
+    LegalDisputesCount,
+    AltmanZ,
+    OhlsonO,
+    Zmijewski,
+    RevenueCagr,
+    MarginTrend,
+    DebtRatioChange,
+    RevenueVolatility,
+    Industry,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FactorRule {
+    /// 分段打分,按顺序取第一个命中的区间
+    Bands {
+        bands: Vec<Band>,
+        #[serde(default)]
+        default_points: f64,
+        #[serde(default)]
+        default_label: Option<String>,
+    },
+    /// 扣分项: -coefficient * transform(min(value, cap))
+    Penalty {
+        coefficient: f64,
+        #[serde(default)]
+        cap: Option<f64>,
+        #[serde(default)]
+        transform: Transform,
+    },
+    /// 枚举取值打分,如行业
+    Lookup {
+        groups: Vec<LookupGroup>,
+        #[serde(default)]
+        default_points: f64,
+        #[serde(default)]
+        default_label: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Band {
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    pub points: f64,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transform {
+    #[default]
+    Linear,
+    Sqrt,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LookupGroup {
+    pub values: Vec<String>,
+    pub points: f64,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScorecardInfo {
+    #[serde(flatten)]
+    pub id: ScorecardId,
+    pub description: String,
+    pub source: String,
+    pub active: bool,
+}
+
+pub enum InputValue<'a> {
+    Number(f64),
+    Text(&'a str),
+    /// 可选字段未提供
+    Missing,
+}
+
+/// 单次评分的完整结果
+pub struct Evaluation {
+    pub credit_score: f64,
+    pub score_details: ScoreDetails,
+    pub factor_details: Vec<FactorContribution>,
+    pub reason_codes: Vec<ReasonCode>,
+}
+
+fn default_min_score() -> f64 {
+    0.0
+}
+
+fn default_max_score() -> f64 {
+    100.0
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn optional<'a>(value: Option<f64>) -> InputValue<'a> {
+    value.map_or(InputValue::Missing, InputValue::Number)
+}
+
+impl FactorInput {
+    pub fn is_numeric(&self) -> bool {
+        !matches!(self, FactorInput::Industry)
+    }
+
+    pub fn value<'a>(&self, company: &'a CompanyData) -> InputValue<'a> {
+        let number = match self {
+            FactorInput::Revenue => company.revenue,
+            FactorInput::NetProfit => company.net_profit,
+            FactorInput::ProfitMargin => {
+                if company.revenue > 0.0 {
+                    company.net_profit / company.revenue
+                } else {
+                    0.0
+                }
+            }
+            FactorInput::TotalAssets => company.total_assets,
+            FactorInput::TotalLiabilities => company.total_liabilities,
+            FactorInput::DebtToAssetRatio => company.debt_to_asset_ratio,
+            FactorInput::RAndDRatio => company.r_and_d_ratio,
+            FactorInput::PatentCount => company.patent_count as f64,
+            FactorInput::UpstreamCoreCompanies => company.upstream_core_companies as f64,
+            FactorInput::DownstreamCustomers => company.downstream_customers as f64,
+            FactorInput::OverdueCount => company.overdue_count as f64,
+            FactorInput::LegalDisputesCount => company.legal_disputes_count as f64,
+            FactorInput::AltmanZ => return optional(altman_z(company)),
+            FactorInput::OhlsonO => return optional(ohlson_o(company)),
+            FactorInput::Zmijewski => return optional(zmijewski(company)),
+            FactorInput::RevenueCagr => return optional(revenue_cagr(company)),
+            FactorInput::MarginTrend => return optional(margin_trend(company)),
+            FactorInput::DebtRatioChange => return optional(debt_ratio_change(company)),
+            FactorInput::RevenueVolatility => return optional(revenue_volatility(company)),
+            FactorInput::Industry => return InputValue::Text(&company.industry),
+        };
+        InputValue::Number(number)
+    }
+}
+
+impl Band {
+    fn contains(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+
+    fn label(&self) -> String {
+        if let Some(label) = &self.label {
+            return label.clone();
+        }
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => format!("{}~{}", min, max),
+            (Some(min), None) => format!("≥{}", min),
+            (None, Some(max)) => format!("≤{}", max),
+            (None, None) => "全部".into(),
+        }
+    }
+}
+
+fn format_number(x: f64) -> String {
+    if x.fract() == 0.0 {
+        format!("{:.0}", x)
+    } else {
+        format!("{:.2}", x)
+    }
+}
+
+impl Factor {
+    /// 计算单个因子的取值、命中区间及得分(已乘权重)
+    pub fn evaluate(&self, company: &CompanyData, category: &str) -> FactorContribution {
+        let value = self.input.value(company);
+        let (points, max_points, band) = match (&self.rule, &value) {
+            (
+                FactorRule::Bands {
+                    bands,
+                    default_points,
+                    default_label,
+                },
+                InputValue::Number(x),
+            ) => {
+                let max_points = bands
+                    .iter()
+                    .map(|b| b.points)
+                    .fold(*default_points, f64::max);
+                match bands.iter().find(|b| b.contains(*x)) {
+                    Some(band) => (band.points, max_points, band.label()),
+                    None => (
+                        *default_points,
+                        max_points,
+                        default_label.clone().unwrap_or_else(|| "其他".into()),
+                    ),
+                }
+            }
+            (
+                FactorRule::Penalty {
+                    coefficient,
+                    cap,
+                    transform,
+                },
+                InputValue::Number(x),
+            ) => {
+                let capped = cap.map_or(*x, |c| x.min(c));
+                let units = match transform {
+                    Transform::Linear => capped,
+                    Transform::Sqrt => capped.sqrt(),
+                };
+                let band = match cap {
+                    Some(c) if *x > *c => {
+                        format!("{}(按{}计)", format_number(*x), format_number(*c))
+                    }
+                    _ => format_number(*x),
+                };
+                (-coefficient * units, 0.0, band)
+            }
+            (
+                FactorRule::Lookup {
+                    groups,
+                    default_points,
+                    default_label,
+                },
+                InputValue::Text(s),
+            ) => {
+                let max_points = groups
+                    .iter()
+                    .map(|g| g.points)
+                    .fold(*default_points, f64::max);
+                match groups.iter().find(|g| g.values.iter().any(|v| v == s)) {
+                    Some(group) => (
+                        group.points,
+                        max_points,
+                        group.label.clone().unwrap_or_else(|| s.to_string()),
+                    ),
+                    None => (
+                        *default_points,
+                        max_points,
+                        default_label.clone().unwrap_or_else(|| "其他".into()),
+                    ),
+                }
+            }
+            // 缺失的可选指标不计分,也不计入失分
+            (_, InputValue::Missing) => (0.0, 0.0, "数据缺失".into()),
+            _ => (0.0, 0.0, String::new()),
+        };
+
+        FactorContribution {
+            key: self.key.clone(),
+            name: self.name.clone(),
+            category: category.to_string(),
+            value: match value {
+                InputValue::Number(x) => FactorValue::Number(x),
+                InputValue::Text(s) => FactorValue::Text(s.to_string()),
+                InputValue::Missing => FactorValue::Missing,
+            },
+            band,
+            points: points * self.weight,
+            max_points: max_points * self.weight,
+        }
+    }
+
+    fn reason(&self, contribution: &FactorContribution) -> ReasonCode {
+        let value = match &contribution.value {
+            FactorValue::Number(x) => format_number(*x),
+            FactorValue::Text(s) => s.clone(),
+            FactorValue::Missing => "缺失".into(),
+        };
+        let message = self
+            .reason
+            .as_deref()
+            .unwrap_or("{name}:{band}")
+            .replace("{name}", &self.name)
+            .replace("{value}", &value)
+            .replace("{band}", &contribution.band);
+
+        ReasonCode {
+            code: self.code.clone().unwrap_or_else(|| self.key.to_uppercase()),
+            factor: self.key.clone(),
+            message,
+            points_lost: contribution.max_points - contribution.points,
+        }
+    }
+}
+
+impl Category {
+    /// 计算分类原始得分(含基础分及上下限)
+    fn total(&self, factors: &[FactorContribution]) -> f64 {
+        let mut points = self.base_points;
+        for factor in factors {
+            points += factor.points;
+        }
+        if let Some(floor) = self.floor {
+            points = points.max(floor);
+        }
+        if let Some(cap) = self.cap {
+            points = points.min(cap);
+        }
+        points
+    }
+
+    fn normalize(&self, points: f64) -> f64 {
+        match self.max_points {
+            Some(max) => (points / max) * 100.0,
+            None => points,
+        }
+    }
+}
+
+/// 紧凑且对象键按字典序递归排列的 JSON,不依赖 serde_json 的 Map 是否保留插入顺序
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::from(key.as_str()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+impl Scorecard {
+    pub fn from_json(content: &str) -> Result<Self, String> {
+        let value: Value =
+            serde_json::from_str(content).map_err(|e| format!("评分卡格式错误: {}", e))?;
+        let mut canonical = String::new();
+        write_canonical(&value, &mut canonical);
+        let mut scorecard: Scorecard =
+            serde_json::from_value(value).map_err(|e| format!("评分卡格式错误: {}", e))?;
+        scorecard.validate()?;
+
+        // 以输入内容的规范形式计算哈希,与文件缩进、字段顺序无关
+        scorecard.hash = Sha256::digest(canonical.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        Ok(scorecard)
+    }
+
+    pub fn id(&self) -> ScorecardId {
+        ScorecardId {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            hash: self.hash.clone(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let fail = |msg: String| Err(format!("评分卡 {} 校验失败: {}", self.name, msg));
+
+        if self.name.trim().is_empty() {
+            return Err("评分卡名称不能为空".into());
+        }
+        if self.version.trim().is_empty() {
+            return fail("版本号不能为空".into());
+        }
+        // 名称和版本号随评分结果写入链上账户,长度受账户空间限制
+        if self.name.len() > ScorecardRef::MAX_NAME_LEN {
+            return fail(format!("名称超过 {} 字节", ScorecardRef::MAX_NAME_LEN));
+        }
+        if self.version.len() > ScorecardRef::MAX_VERSION_LEN {
+            return fail(format!("版本号超过 {} 字节", ScorecardRef::MAX_VERSION_LEN));
+        }
+        // 名称和版本号用作评分卡目录下的文件名
+        for (label, value) in [("名称", &self.name), ("版本号", &self.version)] {
+            if value.starts_with('.')
+                || !value
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            {
+                return fail(format!(
+                    "{} {} 只能包含字母、数字、'-'、'_' 和 '.',且不能以 '.' 开头",
+                    label, value
+                ));
+            }
+        }
+        if self.name == "manual" {
+            return fail("名称 manual 保留给手动修改的评分".into());
+        }
+        if !(self.min_score.is_finite() && self.max_score.is_finite())
+            || self.min_score >= self.max_score
+        {
+            return fail("min_score 必须小于 max_score".into());
+        }
+        if self.categories.is_empty() {
+            return fail("至少需要一个评分分类".into());
+        }
+
+        let mut category_keys = HashSet::new();
+        let mut factor_keys = HashSet::new();
+        for category in &self.categories {
+            if !category_keys.insert(category.key) {
+                return fail(format!("分类 {:?} 重复", category.key));
+            }
+            if category
+                .max_points
+                .is_some_and(|m| !(m.is_finite() && m > 0.0))
+            {
+                return fail(format!("分类 {} 的 max_points 必须大于 0", category.name));
+            }
+            if let (Some(floor), Some(cap)) = (category.floor, category.cap) {
+                if floor > cap {
+                    return fail(format!("分类 {} 的 floor 大于 cap", category.name));
+                }
+            }
+            if category.factors.is_empty() {
+                return fail(format!("分类 {} 没有评分因子", category.name));
+            }
+
+            for factor in &category.factors {
+                if !factor_keys.insert(factor.key.as_str()) {
+                    return fail(format!("因子 {} 重复", factor.key));
+                }
+                if !(factor.weight.is_finite() && factor.weight >= 0.0) {
+                    return fail(format!("因子 {} 的权重无效", factor.key));
+                }
+                match &factor.rule {
+                    FactorRule::Bands { bands, .. } => {
+                        if !factor.input.is_numeric() {
+                            return fail(format!("因子 {} 的分段规则需要数值输入", factor.key));
+                        }
+                        if bands.is_empty() {
+                            return fail(format!("因子 {} 没有定义区间", factor.key));
+                        }
+                        for band in bands {
+                            if band.min.is_none() && band.max.is_none() {
+                                return fail(format!("因子 {} 存在无边界区间", factor.key));
+                            }
+                            if let (Some(min), Some(max)) = (band.min, band.max) {
+                                if min > max {
+                                    return fail(format!("因子 {} 区间下限大于上限", factor.key));
+                                }
+                            }
+                        }
+                    }
+                    FactorRule::Penalty {
+                        coefficient, cap, ..
+                    } => {
+                        if !factor.input.is_numeric() {
+                            return fail(format!("因子 {} 的扣分规则需要数值输入", factor.key));
+                        }
+                        if !coefficient.is_finite()
+                            || *coefficient < 0.0
+                            || cap.is_some_and(|c| c < 0.0)
+                        {
+                            return fail(format!("因子 {} 的扣分参数无效", factor.key));
+                        }
+                    }
+                    FactorRule::Lookup { groups, .. } => {
+                        if factor.input.is_numeric() {
+                            return fail(format!("因子 {} 的枚举规则需要文本输入", factor.key));
+                        }
+                        let mut seen = HashSet::new();
+                        for value in groups.iter().flat_map(|g| &g.values) {
+                            if !seen.insert(value.as_str()) {
+                                return fail(format!("因子 {} 的取值 {} 重复", factor.key, value));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 行业查找表中列出的行业,按规则中的顺序去重
+    pub fn industries(&self) -> Vec<String> {
+        let mut industries: Vec<String> = Vec::new();
+        for factor in self.categories.iter().flat_map(|c| &c.factors) {
+            let FactorRule::Lookup { groups, .. } = &factor.rule else {
+                continue;
+            };
+            if factor.input != FactorInput::Industry {
+                continue;
+            }
+            for value in groups.iter().flat_map(|g| &g.values) {
+                if !industries.contains(value) {
+                    industries.push(value.clone());
+                }
+            }
+        }
+        industries
+    }
+
+    /// 按评分卡计算总分、归一化后的分类得分、因子明细及扣分理由
+    pub fn evaluate(&self, company: &CompanyData) -> Evaluation {
+        let mut score_details = ScoreDetails {
+            financial_score: 0.0,
+            innovation_score: 0.0,
+            supply_chain_score: 0.0,
+            risk_score: 0.0,
+            industry_adjustment: 0.0,
+            trend_adjustment: 0.0,
+        };
+        let mut factor_details = Vec::new();
+        let mut reason_codes = Vec::new();
+
+        let mut total_score = 0.0;
+        for category in &self.categories {
+            let contributions: Vec<FactorContribution> = category
+                .factors
+                .iter()
+                .map(|f| f.evaluate(company, &category.name))
+                .collect();
+            let points = category.total(&contributions);
+            total_score += points;
+
+            let normalized = category.normalize(points);
+            match category.key {
+                CategoryKey::Financial => score_details.financial_score = normalized,
+                CategoryKey::Innovation => score_details.innovation_score = normalized,
+                CategoryKey::SupplyChain => score_details.supply_chain_score = normalized,
+                CategoryKey::Risk => score_details.risk_score = normalized,
+                CategoryKey::IndustryAdjustment => score_details.industry_adjustment = normalized,
+                CategoryKey::Trend => score_details.trend_adjustment = normalized,
+            }
+
+            for (factor, contribution) in category.factors.iter().zip(&contributions) {
+                let reason = factor.reason(contribution);
+                if reason.points_lost > 1e-9 {
+                    reason_codes.push(reason);
+                }
+            }
+            factor_details.extend(contributions);
+        }
+
+        // 按失分从多到少排序,失分相同时保持评分卡中的顺序
+        reason_codes.sort_by(|a, b| b.points_lost.total_cmp(&a.points_lost));
+
+        Evaluation {
+            credit_score: total_score.max(self.min_score).min(self.max_score),
+            score_details,
+            factor_details,
+            reason_codes,
+        }
+    }
+}
+
+struct RegistryEntry {
+    scorecard: Arc<Scorecard>,
+    source: String,
+}
+
+/// 评分卡注册表,以内容哈希为键,同名评分卡的各个版本并存
+struct ScorecardRegistry {
+    dir: Option<PathBuf>,
+    entries: HashMap<String, RegistryEntry>,
+    active: String,
+}
+
+impl ScorecardRegistry {
+    fn new() -> Self {
+        let default = Scorecard::from_json(DEFAULT_SCORECARD).expect("内置评分卡格式错误");
+        let active = default.hash.clone();
+        let mut entries = HashMap::new();
+        entries.insert(
+            default.hash.clone(),
+            RegistryEntry {
+                scorecard: Arc::new(default),
+                source: "内置".into(),
+            },
+        );
+        Self {
+            dir: None,
+            entries,
+            active,
+        }
+    }
+
+    fn info(&self, hash: &str) -> Option<ScorecardInfo> {
+        self.entries.get(hash).map(|entry| ScorecardInfo {
+            id: entry.scorecard.id(),
+            description: entry.scorecard.description.clone(),
+            source: entry.source.clone(),
+            active: hash == self.active,
+        })
+    }
+
+    fn find(&self, name: &str, version: &str) -> Option<&RegistryEntry> {
+        self.entries
+            .values()
+            .find(|e| e.scorecard.name == name && e.scorecard.version == version)
+    }
+
+    /// 同名同版本但内容不同的评分卡会导致历史结果无法复现,必须拒绝
+    fn check_conflict(&self, scorecard: &Scorecard) -> Result<(), String> {
+        match self.find(&scorecard.name, &scorecard.version) {
+            Some(existing) if existing.scorecard.hash != scorecard.hash => Err(format!(
+                "评分卡 {} 版本 {} 已存在且内容不同,请提升版本号",
+                scorecard.name, scorecard.version
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn register(&mut self, scorecard: Scorecard, source: String) -> Result<String, String> {
+        self.check_conflict(&scorecard)?;
+        let hash = scorecard.hash.clone();
+        self.entries.entry(hash.clone()).or_insert(RegistryEntry {
+            scorecard: Arc::new(scorecard),
+            source,
+        });
+        Ok(hash)
+    }
+}
+
+fn read_scorecard_file(path: &Path) -> Result<Scorecard, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("无法读取评分卡 {}: {}", path.display(), e))?;
+    Scorecard::from_json(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// 启动时加载目录下的全部评分卡,无效文件会被跳过
+pub fn init_scorecards(dir: PathBuf) -> Result<(), String> {
+    fs::create_dir_all(&dir).map_err(|e| format!("无法创建评分卡目录: {}", e))?;
+
+    let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+    let entries = fs::read_dir(&dir).map_err(|e| format!("无法读取评分卡目录: {}", e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let result = read_scorecard_file(&path)
+            .and_then(|scorecard| registry.register(scorecard, path.display().to_string()));
+        if let Err(e) = result {
+            eprintln!("加载评分卡失败: {}", e);
+        }
+    }
+
+    if let Ok(active) = fs::read_to_string(dir.join(ACTIVE_FILE)) {
+        let active = active.trim();
+        if registry.entries.contains_key(active) {
+            registry.active = active.to_string();
+        }
+    }
+    registry.dir = Some(dir);
+
+    Ok(())
+}
+
+/// 当前生效的评分卡
+pub fn active_scorecard() -> Arc<Scorecard> {
+    let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+    registry.entries[&registry.active].scorecard.clone()
+}
+
+/// 按内容哈希查找评分卡,用于复现历史评分
+pub fn find_scorecard(hash: &str) -> Result<Arc<Scorecard>, String> {
+    let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+    registry
+        .entries
+        .get(hash)
+        .map(|e| e.scorecard.clone())
+        .ok_or_else(|| format!("未找到哈希为 {} 的评分卡", hash))
+}
+
+pub fn all_scorecards() -> Vec<ScorecardInfo> {
+    let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+    let mut infos: Vec<ScorecardInfo> = registry
+        .entries
+        .keys()
+        .filter_map(|hash| registry.info(hash))
+        .collect();
+    infos.sort_by(|a, b| (&a.id.name, &a.id.version).cmp(&(&b.id.name, &b.id.version)));
+    infos
+}
+
+/// 校验并加载评分卡文件,同时保存到评分卡目录以便下次启动自动加载
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+pub fn register_scorecard_file(path: &str) -> Result<ScorecardInfo, String> {
+    let scorecard = read_scorecard_file(Path::new(path))?;
+
+    let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+    registry.check_conflict(&scorecard)?;
+    let source = match &registry.dir {
+        Some(dir) => {
+            let target = dir.join(format!("{}-{}.json", scorecard.name, scorecard.version));
+            // 选中的正是评分卡目录下的同名文件时不复制,否则复制会先清空文件
+            if !same_file(Path::new(path), &target) {
+                fs::copy(path, &target).map_err(|e| format!("保存评分卡失败: {}", e))?;
+            }
+            target.display().to_string()
+        }
+        None => path.to_string(),
+    };
+    let hash = registry.register(scorecard, source)?;
+
+    Ok(registry.info(&hash).expect("评分卡已注册"))
+}
+
+pub fn activate_scorecard(name: &str, version: &str) -> Result<ScorecardInfo, String> {
+    let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+    let hash = registry
+        .find(name, version)
+        .map(|e| e.scorecard.hash.clone())
+        .ok_or_else(|| format!("评分卡 {} 版本 {} 不存在", name, version))?;
+    registry.active = hash.clone();
+
+    if let Some(dir) = &registry.dir {
+        fs::write(dir.join(ACTIVE_FILE), &hash)
+            .map_err(|e| format!("保存当前评分卡失败: {}", e))?;
+    }
+
+    Ok(registry.info(&hash).expect("评分卡已注册"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 引入评分卡之前写死在代码中的评分规则,返回 (总分, 各维度原始得分)
+    fn baseline_score(c: &CompanyData) -> (f64, [f64; 5]) {
+        let margin = if c.revenue > 0.0 {
+            c.net_profit / c.revenue
+        } else {
+            0.0
+        };
+        let step = |x: f64, tiers: &[(f64, f64)], floor: f64| {
+            tiers
+                .iter()
+                .find(|(t, _)| x >= *t)
+                .map_or(floor, |(_, points)| *points)
+        };
+        let debt = match c.debt_to_asset_ratio {
+            x if x <= 30.0 => 15.0,
+            x if x <= 50.0 => 12.0,
+            x if x <= 70.0 => 8.0,
+            x if x <= 85.0 => 4.0,
+            _ => 0.0,
+        };
+        let financial = step(
+            margin,
+            &[(0.15, 15.0), (0.10, 12.0), (0.05, 8.0), (0.0, 5.0)],
+            0.0,
+        ) + debt
+            + step(
+                c.total_assets,
+                &[
+                    (50000.0, 10.0),
+                    (20000.0, 8.0),
+                    (10000.0, 6.0),
+                    (5000.0, 4.0),
+                ],
+                2.0,
+            );
+        let innovation = step(
+            c.r_and_d_ratio,
+            &[(15.0, 15.0), (10.0, 12.0), (5.0, 8.0), (3.0, 5.0)],
+            2.0,
+        ) + step(
+            c.patent_count as f64,
+            &[
+                (50.0, 15.0),
+                (20.0, 12.0),
+                (10.0, 9.0),
+                (5.0, 6.0),
+                (1.0, 3.0),
+            ],
+            0.0,
+        );
+        let supply_chain = step(
+            c.upstream_core_companies as f64,
+            &[(5.0, 10.0), (3.0, 8.0), (2.0, 6.0), (1.0, 4.0)],
+            0.0,
+        ) + step(
+            c.downstream_customers as f64,
+            &[(20.0, 10.0), (10.0, 8.0), (5.0, 6.0), (3.0, 4.0)],
+            2.0,
+        );
+        let risk = (10.0
+            - 2.0 * (c.overdue_count as f64).min(5.0).sqrt()
+            - 3.0 * (c.legal_disputes_count as f64).min(5.0).sqrt())
+        .max(2.0);
+        let industry = match c.industry.as_str() {
+            "人工智能" | "新能源" | "生物医药" | "新材料" => 5.0,
+            "高端制造" | "信息技术" | "节能环保" => 3.0,
+            "传统制造" | "房地产" => -3.0,
+            "高污染" | "高能耗" => -5.0,
+            _ => 0.0,
+        };
+        let total = financial + innovation + supply_chain + risk + industry;
+        (
+            total.clamp(0.0, 100.0),
+            [financial, innovation, supply_chain, risk, industry],
+        )
+    }
+
+    fn company(industry: &str, figures: [f64; 5], counts: [i32; 5]) -> CompanyData {
+        let [revenue, net_profit, total_assets, debt_to_asset_ratio, r_and_d_ratio] = figures;
+        let [patent_count, upstream, downstream, overdue, disputes] = counts;
+        CompanyData {
+            company_id: "C1".into(),
+            company_name: "甲".into(),
+            industry: industry.into(),
+            revenue,
+            net_profit,
+            total_assets,
+            total_liabilities: total_assets * debt_to_asset_ratio / 100.0,
+            debt_to_asset_ratio,
+            r_and_d_ratio,
+            patent_count,
+            upstream_core_companies: upstream,
+            downstream_customers: downstream,
+            overdue_count: overdue,
+            legal_disputes_count: disputes,
+            working_capital: None,
+            retained_earnings: None,
+            ebit: None,
+            current_assets: None,
+            current_liabilities: None,
+            operating_cash_flow: None,
+            fiscal_year: None,
+            history: Vec::new(),
+            original_units: Default::default(),
+        }
+    }
+
+    #[test]
+    fn default_scorecard_reproduces_baseline_rules() {
+        let scorecard = Scorecard::from_json(DEFAULT_SCORECARD).unwrap();
+        let industries = ["人工智能", "高端制造", "传统制造", "高污染", "其他"];
+        for (i, industry) in industries.iter().enumerate() {
+            for net_profit in [-100.0, 49.0, 100.0, 150.0] {
+                for total_assets in [4999.0, 5000.0, 20000.0, 60000.0] {
+                    for debt in [30.0, 30.1, 70.0, 85.1] {
+                        for (j, patents) in [0, 1, 10, 60].into_iter().enumerate() {
+                            let c = company(
+                                industry,
+                                [1000.0, net_profit, total_assets, debt, 5.0 * j as f64],
+                                [patents, i as i32, patents / 2, j as i32 * 2, i as i32],
+                            );
+                            let (total, raw) = baseline_score(&c);
+                            let evaluation = scorecard.evaluate(&c);
+                            let details = &evaluation.score_details;
+                            assert_eq!(evaluation.credit_score, total, "{:?}", c);
+                            assert_eq!(details.financial_score, raw[0] / 40.0 * 100.0);
+                            assert_eq!(details.innovation_score, raw[1] / 30.0 * 100.0);
+                            assert_eq!(details.supply_chain_score, raw[2] / 20.0 * 100.0);
+                            assert_eq!(details.risk_score, raw[3] / 10.0 * 100.0);
+                            assert_eq!(details.industry_adjustment, raw[4]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hash_ignores_formatting_and_key_order() {
+        let original = Scorecard::from_json(DEFAULT_SCORECARD).unwrap();
+        let mut value: Value = serde_json::from_str(DEFAULT_SCORECARD).unwrap();
+        let Value::Object(fields) = &mut value else {
+            panic!("评分卡应为对象");
+        };
+        // 键倒序后重新组成紧凑 JSON
+        let reordered: Vec<String> = fields
+            .iter()
+            .rev()
+            .map(|(k, v)| format!("{}:{}", Value::from(k.as_str()), v))
+            .collect();
+        let compact = format!("{{{}}}", reordered.join(","));
+        assert_eq!(Scorecard::from_json(&compact).unwrap().hash, original.hash);
+
+        fields.insert("description".into(), Value::from("修改说明"));
+        let changed = Scorecard::from_json(&value.to_string()).unwrap();
+        assert_ne!(changed.hash, original.hash);
+    }
+
+    #[test]
+    fn same_file_resolves_relative_components() {
+        let dir = std::env::temp_dir().join(format!("scorecards-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("default-1.0.json");
+        fs::write(&file, DEFAULT_SCORECARD).unwrap();
+
+        let same = same_file(&dir.join(".").join("default-1.0.json"), &file);
+        let other = same_file(&dir.join("other.json"), &file);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(same);
+        assert!(!other);
+    }
+}
@@ -0,0 +1,402 @@
+use crate::excel::calc::{
+    calculate_credit_score, get_credit_rating, rating_rank, score_company, RATING_GRADES,
+};
+use crate::excel::history::sync_latest_period;
+use crate::excel::scorecard::Scorecard;
+use crate::excel::types::{
+    CompanyData, CompanyDataEn, CompanyWithScore, ScoreSnapshot, SensitivityItem,
+    SensitivityResult, WhatIfResult,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 将字段覆盖值应用到企业数据上,字段名与 CompanyDataEn 一致;
+/// 同一年度的最新一期历史数据随之更新,趋势因子反映调整后的数值
+pub fn apply_overrides(
+    company: &CompanyData,
+    overrides: &HashMap<String, Value>,
+) -> Result<CompanyData, String> {
+    let mut value =
+        serde_json::to_value(CompanyDataEn::from(company.clone())).map_err(|e| e.to_string())?;
+    let fields = value.as_object_mut().ok_or("企业数据格式错误")?;
+
+    for (field, new_value) in overrides {
+        if field == "company_id" || field == "company_name" {
+            return Err(format!("字段 {} 不支持模拟调整", field));
+        }
+        match fields.get_mut(field) {
+            Some(slot) => *slot = new_value.clone(),
+            None => return Err(format!("未知字段: {}", field)),
+        }
+    }
+
+    let company: CompanyDataEn =
+        serde_json::from_value(value).map_err(|e| format!("调整值类型错误: {}", e))?;
+    let mut company = CompanyData::from(company);
+    sync_latest_period(&mut company);
+    Ok(company)
+}
+
+impl From<&CompanyWithScore> for ScoreSnapshot {
+    fn from(c: &CompanyWithScore) -> Self {
+        Self {
+            credit_score: c.credit_score,
+            credit_rating: c.credit_rating.clone(),
+            credit_limit: c.credit_limit.clone(),
+            risk_level: c.risk_level.clone(),
+        }
+    }
+}
+
+/// 对比调整前后的评分结果
+pub fn simulate_what_if(
+    company: CompanyData,
+    overrides: &HashMap<String, Value>,
+    scorecard: &Scorecard,
+) -> Result<WhatIfResult, String> {
+    let adjusted = apply_overrides(&company, overrides)?;
+
+    let original = score_company(company, scorecard);
+    let simulated = score_company(adjusted, scorecard);
+
+    Ok(WhatIfResult {
+        score_change: simulated.credit_score - original.credit_score,
+        original: (&original).into(),
+        simulated: (&simulated).into(),
+        company_data: simulated.company_data.into(),
+    })
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    Increase,
+    Decrease,
+}
+
+/// 可调整的单一字段
+struct Lever {
+    field: &'static str,
+    name: &'static str,
+    direction: Direction,
+    integer: bool,
+    get: fn(&CompanyData) -> f64,
+    set: fn(&mut CompanyData, f64),
+    /// 改善方向上的搜索边界
+    limit: fn(&CompanyData) -> f64,
+}
+
+const LEVERS: &[Lever] = &[
+    Lever {
+        field: "net_profit",
+        name: "净利润(万元)",
+        direction: Direction::Increase,
+        integer: false,
+        get: |c| c.net_profit,
+        set: |c, v| c.net_profit = v,
+        limit: |c| c.revenue.max(c.net_profit),
+    },
+    Lever {
+        field: "total_assets",
+        name: "资产总额(万元)",
+        direction: Direction::Increase,
+        integer: false,
+        get: |c| c.total_assets,
+        set: |c, v| c.total_assets = v,
+        limit: |c| (c.total_assets * 10.0).max(100000.0),
+    },
+    Lever {
+        field: "debt_to_asset_ratio",
+        name: "资产负债率(%)",
+        direction: Direction::Decrease,
+        integer: false,
+        get: |c| c.debt_to_asset_ratio,
+        set: |c, v| c.debt_to_asset_ratio = v,
+        limit: |_| 0.0,
+    },
+    Lever {
+        field: "r_and_d_ratio",
+        name: "研发投入占比(%)",
+        direction: Direction::Increase,
+        integer: false,
+        get: |c| c.r_and_d_ratio,
+        set: |c, v| c.r_and_d_ratio = v,
+        limit: |c| c.r_and_d_ratio.max(100.0),
+    },
+    Lever {
+        field: "patent_count",
+        name: "专利数量",
+        direction: Direction::Increase,
+        integer: true,
+        get: |c| c.patent_count as f64,
+        set: |c, v| c.patent_count = v as i32,
+        limit: |c| (c.patent_count as f64 * 10.0).max(100.0),
+    },
+    Lever {
+        field: "upstream_core_companies",
+        name: "上游核心企业数量",
+        direction: Direction::Increase,
+        integer: true,
+        get: |c| c.upstream_core_companies as f64,
+        set: |c, v| c.upstream_core_companies = v as i32,
+        limit: |c| c.upstream_core_companies as f64 + 20.0,
+    },
+    Lever {
+        field: "downstream_customers",
+        name: "下游客户数量",
+        direction: Direction::Increase,
+        integer: true,
+        get: |c| c.downstream_customers as f64,
+        set: |c, v| c.downstream_customers = v as i32,
+        limit: |c| c.downstream_customers as f64 + 50.0,
+    },
+    Lever {
+        field: "overdue_count",
+        name: "历史逾期次数",
+        direction: Direction::Decrease,
+        integer: true,
+        get: |c| c.overdue_count as f64,
+        set: |c, v| c.overdue_count = v as i32,
+        limit: |_| 0.0,
+    },
+    Lever {
+        field: "legal_disputes_count",
+        name: "法律诉讼次数",
+        direction: Direction::Decrease,
+        integer: true,
+        get: |c| c.legal_disputes_count as f64,
+        set: |c, v| c.legal_disputes_count = v as i32,
+        limit: |_| 0.0,
+    },
+];
+
+/// 线性扫描的步数,找到达标区间后再二分求精
+const SCAN_STEPS: usize = 200;
+const BISECT_ITERATIONS: usize = 50;
+
+fn rated(company: &CompanyData, scorecard: &Scorecard) -> (f64, String, usize) {
+    let score = calculate_credit_score(company, scorecard).credit_score;
+    let (rating, _, _) = get_credit_rating(score);
+    let rank = rating_rank(&rating).unwrap_or(0);
+    (score, rating, rank)
+}
+
+impl Lever {
+    /// 求达到目标评级所需的最小调整值
+    fn required_value(
+        &self,
+        company: &CompanyData,
+        scorecard: &Scorecard,
+        target: usize,
+    ) -> Option<f64> {
+        let current = (self.get)(company);
+        let limit = (self.limit)(company);
+        let improving = match self.direction {
+            Direction::Increase => limit > current,
+            Direction::Decrease => limit < current,
+        };
+        if !improving {
+            return None;
+        }
+
+        let mut probe = company.clone();
+        let mut reaches = |value: f64| {
+            (self.set)(&mut probe, value);
+            sync_latest_period(&mut probe);
+            rated(&probe, scorecard).2 >= target
+        };
+
+        if self.integer {
+            let sign = match self.direction {
+                Direction::Increase => 1.0,
+                Direction::Decrease => -1.0,
+            };
+            let at = |k: usize| current + sign * k as f64;
+            // 与连续字段相同,先按步长扫描找到达标的区间,再二分出最小的整数调整
+            let steps = (limit - current).abs() as usize;
+            let stride = steps.div_ceil(SCAN_STEPS).max(1);
+            let mut previous = 0;
+            while previous < steps {
+                let k = (previous + stride).min(steps);
+                if reaches(at(k)) {
+                    let (mut lo, mut hi) = (previous, k);
+                    while hi - lo > 1 {
+                        let mid = lo + (hi - lo) / 2;
+                        if reaches(at(mid)) {
+                            hi = mid;
+                        } else {
+                            lo = mid;
+                        }
+                    }
+                    return Some(at(hi));
+                }
+                previous = k;
+            }
+            return None;
+        }
+
+        let delta = (limit - current) / SCAN_STEPS as f64;
+        let mut previous = current;
+        for k in 1..=SCAN_STEPS {
+            let value = current + delta * k as f64;
+            if reaches(value) {
+                let (mut lo, mut hi) = (previous, value);
+                for _ in 0..BISECT_ITERATIONS {
+                    let mid = (lo + hi) / 2.0;
+                    if reaches(mid) {
+                        hi = mid;
+                    } else {
+                        lo = mid;
+                    }
+                }
+                // 向改善方向取两位小数,便于作为建议展示
+                let rounded = match self.direction {
+                    Direction::Increase => (hi * 100.0).ceil() / 100.0,
+                    Direction::Decrease => (hi * 100.0).floor() / 100.0,
+                };
+                return Some(if reaches(rounded) { rounded } else { hi });
+            }
+            previous = value;
+        }
+        None
+    }
+}
+
+/// 计算单一字段提升一个评级所需的最小调整,按相对变化从小到大排序
+pub fn analyze_rating_sensitivity(
+    company: &CompanyData,
+    scorecard: &Scorecard,
+) -> SensitivityResult {
+    let (current_score, current_rating, rank) = rated(company, scorecard);
+    let target_rating = RATING_GRADES.get(rank + 1).map(|g| g.to_string());
+
+    let mut suggestions = Vec::new();
+    if target_rating.is_some() {
+        for lever in LEVERS {
+            let Some(required) = lever.required_value(company, scorecard, rank + 1) else {
+                continue;
+            };
+            let current = (lever.get)(company);
+            let mut adjusted = company.clone();
+            (lever.set)(&mut adjusted, required);
+            sync_latest_period(&mut adjusted);
+            let (new_score, new_rating, _) = rated(&adjusted, scorecard);
+
+            // 当前值为 0 或很小时按 1 计算相对变化,避免除零
+            let change = required - current;
+            suggestions.push(SensitivityItem {
+                field: lever.field.into(),
+                field_name: lever.name.into(),
+                current_value: current,
+                required_value: required,
+                change,
+                relative_change: change.abs() / current.abs().max(1.0),
+                new_score,
+                new_rating,
+            });
+        }
+        suggestions.sort_by(|a, b| a.relative_change.total_cmp(&b.relative_change));
+    }
+
+    SensitivityResult {
+        current_score,
+        current_rating,
+        target_rating,
+        suggestions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::excel::history::revenue_cagr;
+    use crate::excel::scorecard::DEFAULT_SCORECARD;
+    use crate::excel::types::FinancialPeriod;
+
+    fn period(fiscal_year: i32, revenue: f64) -> FinancialPeriod {
+        FinancialPeriod {
+            fiscal_year,
+            revenue,
+            net_profit: 100.0,
+            total_assets: 3000.0,
+            total_liabilities: 1200.0,
+            debt_to_asset_ratio: 40.0,
+            r_and_d_ratio: 5.0,
+        }
+    }
+
+    #[test]
+    fn overrides_reach_the_latest_period() {
+        let mut company: CompanyData = serde_json::from_value(serde_json::json!({
+            "企业ID": "C1",
+            "企业名称": "甲",
+            "行业": "人工智能",
+            "营业收入(万元)": 1210.0,
+            "净利润(万元)": 100.0,
+            "资产总额(万元)": 3000.0,
+            "负债总额(万元)": 1200.0,
+            "资产负债率(%)": 40.0,
+            "研发投入占比(%)": 5.0,
+            "专利数量": 3,
+            "上游核心企业数量": 2,
+            "下游客户数量": 5,
+            "历史逾期次数": 0,
+            "法律诉讼次数": 0,
+            "年度": 2023
+        }))
+        .unwrap();
+        company.history = vec![period(2021, 1000.0), period(2023, 1210.0)];
+        let overrides = HashMap::from([("revenue".to_string(), Value::from(640.0))]);
+
+        let adjusted = apply_overrides(&company, &overrides).unwrap();
+
+        assert_eq!(adjusted.history[1].revenue, 640.0);
+        assert!(revenue_cagr(&adjusted).unwrap() < 0.0);
+    }
+
+    #[test]
+    fn integer_levers_find_the_smallest_adjustment() {
+        let scorecard = Scorecard::from_json(DEFAULT_SCORECARD).unwrap();
+        let company: CompanyData = serde_json::from_value(serde_json::json!({
+            "企业ID": "C1",
+            "企业名称": "甲",
+            "行业": "传统制造",
+            "营业收入(万元)": 1000.0,
+            "净利润(万元)": 30.0,
+            "资产总额(万元)": 8000.0,
+            "负债总额(万元)": 6000.0,
+            "资产负债率(%)": 75.0,
+            "研发投入占比(%)": 4.0,
+            "专利数量": 3,
+            "上游核心企业数量": 1,
+            "下游客户数量": 4,
+            "历史逾期次数": 4,
+            "法律诉讼次数": 3
+        }))
+        .unwrap();
+        let (_, _, rank) = rated(&company, &scorecard);
+
+        let mut found = 0;
+        for lever in LEVERS.iter().filter(|l| l.integer) {
+            let current = (lever.get)(&company);
+            let limit = (lever.limit)(&company);
+            let step = if limit > current { 1.0 } else { -1.0 };
+            // 逐个整数检查得到的最小调整
+            let expected = (1..=(limit - current).abs() as usize)
+                .map(|k| current + step * k as f64)
+                .find(|v| {
+                    let mut probe = company.clone();
+                    (lever.set)(&mut probe, *v);
+                    sync_latest_period(&mut probe);
+                    rated(&probe, &scorecard).2 > rank
+                });
+            assert_eq!(
+                lever.required_value(&company, &scorecard, rank + 1),
+                expected,
+                "{}",
+                lever.field
+            );
+            found += usize::from(expected.is_some());
+        }
+        assert!(found > 0);
+    }
+}
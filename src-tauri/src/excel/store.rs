@@ -0,0 +1,607 @@
+use crate::excel::peer::benchmark_peers;
+use crate::excel::types::{
+    BankRecord, BankSubmission, CompanyWithScore, ExcelResult, ImportRecord, ScorecardId,
+};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 按顺序执行的数据库迁移,已执行的数量记录在 user_version 中。
+/// 只能在末尾追加,不能修改已发布的迁移
+const MIGRATIONS: &[&str] = &[
+    // 1: 导入、企业、评分记录、银行申请与批复
+    "CREATE TABLE imports (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        imported_at INTEGER NOT NULL,
+        source TEXT,
+        scorecard_name TEXT NOT NULL,
+        scorecard_version TEXT NOT NULL,
+        scorecard_hash TEXT NOT NULL,
+        total_companies INTEGER NOT NULL
+    );
+    CREATE TABLE import_files (
+        import_id INTEGER NOT NULL REFERENCES imports(id) ON DELETE CASCADE,
+        file TEXT NOT NULL,
+        file_hash TEXT NOT NULL,
+        PRIMARY KEY (import_id, file)
+    );
+    CREATE TABLE companies (
+        company_id TEXT PRIMARY KEY,
+        company_name TEXT NOT NULL,
+        industry TEXT NOT NULL,
+        first_seen INTEGER NOT NULL,
+        last_seen INTEGER NOT NULL
+    );
+    CREATE TABLE score_runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        import_id INTEGER NOT NULL REFERENCES imports(id) ON DELETE CASCADE,
+        company_id TEXT NOT NULL REFERENCES companies(company_id),
+        scored_at INTEGER NOT NULL,
+        credit_score REAL NOT NULL,
+        credit_rating TEXT NOT NULL,
+        credit_limit TEXT NOT NULL,
+        risk_level TEXT NOT NULL,
+        probability_of_default REAL,
+        scorecard_hash TEXT NOT NULL,
+        file TEXT,
+        sheet_name TEXT,
+        row INTEGER,
+        result TEXT NOT NULL
+    );
+    CREATE INDEX score_runs_company ON score_runs(company_id, scored_at);
+    CREATE INDEX score_runs_import ON score_runs(import_id);
+    CREATE TABLE bank_submissions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        company_id TEXT NOT NULL,
+        company_name TEXT NOT NULL,
+        credit_score REAL NOT NULL,
+        credit_rating TEXT NOT NULL,
+        credit_limit TEXT NOT NULL,
+        risk_level TEXT NOT NULL,
+        submitted_at INTEGER NOT NULL
+    );
+    CREATE INDEX bank_submissions_company ON bank_submissions(company_id, submitted_at);
+    CREATE TABLE approvals (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        submission_id INTEGER NOT NULL UNIQUE REFERENCES bank_submissions(id),
+        company_id TEXT NOT NULL,
+        approved_limit REAL NOT NULL,
+        approved_at INTEGER NOT NULL
+    );
+    CREATE INDEX approvals_company ON approvals(company_id, approved_at);",
+    // 2: 授信申请关联对应的评分记录,批复按评分记录区分
+    "ALTER TABLE bank_submissions ADD COLUMN score_run_id INTEGER REFERENCES score_runs(id);
+    CREATE INDEX bank_submissions_run ON bank_submissions(score_run_id);",
+];
+
+static STORE: LazyLock<Mutex<Option<Connection>>> = LazyLock::new(|| Mutex::new(None));
+
+/// 打开本地数据库并执行未完成的迁移
+pub fn init_store(path: PathBuf) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("创建数据目录失败: {}", e))?;
+    }
+    let mut conn =
+        Connection::open(&path).map_err(|e| format!("打开数据库 {:?} 失败: {}", path, e))?;
+    conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;")
+        .map_err(|e| format!("配置数据库失败: {}", e))?;
+    migrate(&mut conn)?;
+    *STORE.lock().unwrap_or_else(PoisonError::into_inner) = Some(conn);
+    Ok(())
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), String> {
+    let version: usize = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(|e| format!("读取数据库版本失败: {}", e))?;
+    if version > MIGRATIONS.len() {
+        return Err(format!(
+            "数据库版本 {} 高于当前程序支持的版本 {},请升级程序",
+            version,
+            MIGRATIONS.len()
+        ));
+    }
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+        // 每个迁移在独立事务中执行,失败时保持之前的版本
+        let apply = |conn: &mut Connection| -> rusqlite::Result<()> {
+            let tx = conn.transaction()?;
+            tx.execute_batch(sql)?;
+            tx.pragma_update(None, "user_version", i + 1)?;
+            tx.commit()
+        };
+        apply(conn).map_err(|e| format!("数据库迁移 {} 失败: {}", i + 1, e))?;
+    }
+    Ok(())
+}
+
+fn with_store<T>(f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>) -> Result<T, String> {
+    let mut store = STORE.lock().unwrap_or_else(PoisonError::into_inner);
+    let conn = store.as_mut().ok_or("本地数据库未初始化")?;
+    f(conn).map_err(|e| format!("数据库操作失败: {}", e))
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// 保存一次导入的全部评分结果,返回导入记录 ID。企业以企业ID为主键,ID 为空时拒绝保存
+pub fn record_import(results: &[ExcelResult], source: Option<&str>) -> Result<i64, String> {
+    let companies: Vec<&CompanyWithScore> = results.iter().flat_map(|r| &r.companies).collect();
+    if let Some(company) = companies
+        .iter()
+        .find(|c| c.company_data.company_id.trim().is_empty())
+    {
+        return Err(match &company.provenance {
+            Some(p) => format!(
+                "{} {} 第 {} 行 企业ID 为空,无法保存",
+                p.file, p.sheet_name, p.row
+            ),
+            None => format!(
+                "企业 {} 的企业ID 为空,无法保存",
+                company.company_data.company_name
+            ),
+        });
+    }
+    let imported_at = companies
+        .iter()
+        .find_map(|c| c.provenance.as_ref())
+        .map_or_else(now, |p| p.imported_at);
+    let scorecard = results
+        .first()
+        .map(|r| r.scorecard.clone())
+        .ok_or("没有可保存的评分结果")?;
+    let serialized = companies
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("序列化评分结果失败: {}", e))?;
+
+    with_store(|conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO imports (imported_at, source, scorecard_name, scorecard_version,
+                 scorecard_hash, total_companies)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                imported_at,
+                source,
+                scorecard.name,
+                scorecard.version,
+                scorecard.hash,
+                companies.len()
+            ],
+        )?;
+        let import_id = tx.last_insert_rowid();
+        insert_score_runs(&tx, import_id, imported_at, &companies, &serialized)?;
+        tx.commit()?;
+        Ok(import_id)
+    })
+}
+
+fn insert_score_runs(
+    tx: &Transaction,
+    import_id: i64,
+    imported_at: i64,
+    companies: &[&CompanyWithScore],
+    serialized: &[String],
+) -> rusqlite::Result<()> {
+    let mut insert_file = tx.prepare(
+        "INSERT OR IGNORE INTO import_files (import_id, file, file_hash) VALUES (?1, ?2, ?3)",
+    )?;
+    let mut upsert_company = tx.prepare(
+        "INSERT INTO companies (company_id, company_name, industry, first_seen, last_seen)
+         VALUES (?1, ?2, ?3, ?4, ?4)
+         ON CONFLICT(company_id) DO UPDATE SET
+             company_name = excluded.company_name,
+             industry = excluded.industry,
+             last_seen = excluded.last_seen",
+    )?;
+    let mut insert_run = tx.prepare(
+        "INSERT INTO score_runs (import_id, company_id, scored_at, credit_score, credit_rating,
+             credit_limit, risk_level, probability_of_default, scorecard_hash, file, sheet_name,
+             row, result)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+    )?;
+    for (company, result) in companies.iter().zip(serialized) {
+        let data = &company.company_data;
+        let provenance = company.provenance.as_ref();
+        if let Some(p) = provenance {
+            insert_file.execute(params![import_id, p.file, p.file_hash])?;
+        }
+        upsert_company.execute(params![
+            data.company_id,
+            data.company_name,
+            data.industry,
+            imported_at
+        ])?;
+        insert_run.execute(params![
+            import_id,
+            data.company_id,
+            imported_at,
+            company.credit_score,
+            company.credit_rating,
+            company.credit_limit,
+            company.risk_level,
+            company.probability_of_default,
+            company.scorecard.hash,
+            provenance.map(|p| &p.file),
+            provenance.map(|p| &p.sheet_name),
+            provenance.map(|p| p.row),
+            result
+        ])?;
+    }
+    Ok(())
+}
+
+/// 列出所有导入记录,最近的在前
+pub fn list_imports() -> Result<Vec<ImportRecord>, String> {
+    with_store(|conn| {
+        let mut files = conn.prepare("SELECT file FROM import_files WHERE import_id = ?1")?;
+        let mut stmt = conn.prepare(
+            "SELECT id, imported_at, source, scorecard_name, scorecard_version, scorecard_hash,
+                 total_companies
+             FROM imports ORDER BY imported_at DESC, id DESC",
+        )?;
+        let mut records = stmt
+            .query_map([], |row| {
+                Ok(ImportRecord {
+                    id: row.get(0)?,
+                    imported_at: row.get(1)?,
+                    source: row.get(2)?,
+                    scorecard: ScorecardId {
+                        name: row.get(3)?,
+                        version: row.get(4)?,
+                        hash: row.get(5)?,
+                    },
+                    files: Vec::new(),
+                    total_companies: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for record in &mut records {
+            record.files = files
+                .query_map([record.id], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+        }
+        Ok(records)
+    })
+}
+
+/// 读取一次导入的评分结果,按工作表分组并重新计算同业对标
+pub fn load_import(import_id: i64) -> Result<Vec<ExcelResult>, String> {
+    let (scorecard, rows) = with_store(|conn| {
+        let scorecard = conn
+            .query_row(
+                "SELECT scorecard_name, scorecard_version, scorecard_hash
+                 FROM imports WHERE id = ?1",
+                [import_id],
+                |row| {
+                    Ok(ScorecardId {
+                        name: row.get(0)?,
+                        version: row.get(1)?,
+                        hash: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?;
+        let mut stmt =
+            conn.prepare("SELECT result FROM score_runs WHERE import_id = ?1 ORDER BY id")?;
+        let rows = stmt
+            .query_map([import_id], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok((scorecard, rows))
+    })?;
+    let scorecard = scorecard.ok_or_else(|| format!("导入记录 {} 不存在", import_id))?;
+
+    // 评分记录按导入时的文件、工作表顺序写入,相邻记录即可分组
+    let mut results: Vec<ExcelResult> = Vec::new();
+    for row in rows {
+        let company: CompanyWithScore =
+            serde_json::from_str(&row).map_err(|e| format!("解析评分记录失败: {}", e))?;
+        let (file, sheet_name) = company
+            .provenance
+            .as_ref()
+            .map(|p| (p.file.clone(), p.sheet_name.clone()))
+            .unwrap_or_default();
+        match results.last_mut() {
+            Some(last) if last.file == file && last.sheet_name == sheet_name => {
+                last.companies.push(company)
+            }
+            _ => results.push(ExcelResult {
+                file,
+                sheet_name,
+                total_companies: 0,
+                scorecard: scorecard.clone(),
+                companies: vec![company],
+                peer_stats: Vec::new(),
+            }),
+        }
+    }
+    for result in &mut results {
+        result.total_companies = result.companies.len();
+    }
+    benchmark_peers(&mut results);
+    Ok(results)
+}
+
+/// 记录提交给银行的授信申请,返回申请 ID;score_run_id 为申请所依据的评分记录
+pub fn record_submission(
+    submission: &BankSubmission,
+    score_run_id: Option<i64>,
+) -> Result<i64, String> {
+    with_store(|conn| {
+        conn.execute(
+            "INSERT INTO bank_submissions (company_id, company_name, credit_score, credit_rating,
+                 credit_limit, risk_level, submitted_at, score_run_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                submission.company_id,
+                submission.company_name,
+                submission.credit_score,
+                submission.credit_rating,
+                submission.credit_limit,
+                submission.risk_level,
+                now(),
+                score_run_id
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    })
+}
+
+/// 记录银行对授信申请的批复额度(万元)
+pub fn record_approval(
+    submission_id: i64,
+    company_id: &str,
+    approved_limit: f64,
+) -> Result<(), String> {
+    with_store(|conn| {
+        conn.execute(
+            "INSERT INTO approvals (submission_id, company_id, approved_limit, approved_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![submission_id, company_id, approved_limit, now()],
+        )?;
+        Ok(())
+    })
+}
+
+/// 企业最近一次的批复额度(万元)
+pub fn latest_approval(company_id: &str) -> Result<Option<f64>, String> {
+    with_store(|conn| {
+        conn.query_row(
+            "SELECT approved_limit FROM approvals WHERE company_id = ?1
+             ORDER BY approved_at DESC, id DESC LIMIT 1",
+            [company_id],
+            |row| row.get(0),
+        )
+        .optional()
+    })
+}
+
+/// 企业在 scored_at 时的评分记录 ID,同一时间有多条时取最后写入的一条;
+/// scored_at 为空时取企业最近一次评分
+pub fn score_run_id(company_id: &str, scored_at: Option<i64>) -> Result<Option<i64>, String> {
+    with_store(|conn| {
+        conn.query_row(
+            "SELECT id FROM score_runs WHERE company_id = ?1 AND (?2 IS NULL OR scored_at = ?2)
+             ORDER BY scored_at DESC, id DESC LIMIT 1",
+            params![company_id, scored_at],
+            |row| row.get(0),
+        )
+        .optional()
+    })
+}
+
+/// 依据某次评分记录提交的申请的批复额度(万元)
+pub fn approval_for_run(score_run_id: i64) -> Result<Option<f64>, String> {
+    with_store(|conn| {
+        conn.query_row(
+            "SELECT a.approved_limit FROM approvals a
+             JOIN bank_submissions s ON s.id = a.submission_id
+             WHERE s.score_run_id = ?1
+             ORDER BY a.approved_at DESC, a.id DESC LIMIT 1",
+            [score_run_id],
+            |row| row.get(0),
+        )
+        .optional()
+    })
+}
+
+/// 企业的授信申请及批复记录,最近的在前
+pub fn bank_history(company_id: &str) -> Result<Vec<BankRecord>, String> {
+    with_store(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT s.id, s.company_id, s.company_name, s.credit_score, s.credit_rating,
+                 s.credit_limit, s.risk_level, s.submitted_at, a.approved_limit, a.approved_at
+             FROM bank_submissions s LEFT JOIN approvals a ON a.submission_id = s.id
+             WHERE s.company_id = ?1
+             ORDER BY s.submitted_at DESC, s.id DESC",
+        )?;
+        let records = stmt
+            .query_map([company_id], |row| {
+                Ok(BankRecord {
+                    id: row.get(0)?,
+                    submission: BankSubmission {
+                        company_id: row.get(1)?,
+                        company_name: row.get(2)?,
+                        credit_score: row.get(3)?,
+                        credit_rating: row.get(4)?,
+                        credit_limit: row.get(5)?,
+                        risk_level: row.get(6)?,
+                    },
+                    submitted_at: row.get(7)?,
+                    approved_limit: row.get(8)?,
+                    approved_at: row.get(9)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(records)
+    })
+}
+
+/// 企业的一次历史评分,result 为评分结果 JSON
+pub struct ScoreRun {
+    pub import_id: i64,
+    pub scored_at: i64,
+    pub result: String,
+}
+
+/// 企业的全部评分记录,按评分时间先后排列
+pub fn company_runs(company_id: &str) -> Result<Vec<ScoreRun>, String> {
+    with_store(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT import_id, scored_at, result FROM score_runs
+             WHERE company_id = ?1 ORDER BY scored_at, id",
+        )?;
+        let runs = stmt
+            .query_map([company_id], |row| {
+                Ok(ScoreRun {
+                    import_id: row.get(0)?,
+                    scored_at: row.get(1)?,
+                    result: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(runs)
+    })
+}
+
+/// 评分记录中的评级
+pub struct RatingRow {
+    pub company_id: String,
+    pub company_name: String,
+    pub scored_at: i64,
+    pub credit_rating: String,
+}
+
+/// until 及之前的全部评级记录,按评分时间先后排列
+pub fn ratings_until(until: i64) -> Result<Vec<RatingRow>, String> {
+    with_store(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT r.company_id, c.company_name, r.scored_at, r.credit_rating
+             FROM score_runs r JOIN companies c ON c.company_id = r.company_id
+             WHERE r.scored_at <= ?1 ORDER BY r.scored_at, r.id",
+        )?;
+        let rows = stmt
+            .query_map([until], |row| {
+                Ok(RatingRow {
+                    company_id: row.get(0)?,
+                    company_name: row.get(1)?,
+                    scored_at: row.get(2)?,
+                    credit_rating: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(rows)
+    })
+}
+
+/// 一次导入中各企业首次评分时的评级,只看该次导入及更早导入中的评分,
+/// 重新查看旧导入时不受之后评分的影响
+pub fn initial_ratings(import_id: i64) -> Result<HashMap<String, String>, String> {
+    with_store(|conn| {
+        // 从晚到早覆盖,保留最早的一条
+        let mut stmt = conn.prepare(
+            "SELECT company_id, credit_rating FROM score_runs
+             WHERE company_id IN (SELECT company_id FROM score_runs WHERE import_id = ?1)
+               AND import_id <= ?1
+               AND scored_at <= (SELECT imported_at FROM imports WHERE id = ?1)
+             ORDER BY scored_at DESC, id DESC",
+        )?;
+        let ratings = stmt
+            .query_map([import_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(ratings)
+    })
+}
+
+/// 一次导入中各企业截至该次导入最近一次的批复额度(万元)。
+/// 导入之前的批复,以及之后依据该次或更早导入的评分提交的申请的批复都计入;
+/// 依据之后导入的评分作出的批复不计入
+pub fn latest_approvals(import_id: i64) -> Result<HashMap<String, f64>, String> {
+    with_store(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT a.company_id, a.approved_limit FROM approvals a
+             JOIN bank_submissions s ON s.id = a.submission_id
+             LEFT JOIN score_runs r ON r.id = s.score_run_id
+             WHERE a.company_id IN (SELECT company_id FROM score_runs WHERE import_id = ?1)
+               AND (a.approved_at <= (SELECT imported_at FROM imports WHERE id = ?1)
+                    OR r.import_id <= ?1)
+             ORDER BY a.approved_at, a.id",
+        )?;
+        let approvals = stmt
+            .query_map([import_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(approvals)
+    })
+}
+
+/// 一次导入中各企业在此之前的评分记录的授信额度,按评分先后排列,元素为 (企业ID, 授信额度)
+pub fn earlier_limits(import_id: i64) -> Result<Vec<(String, String)>, String> {
+    with_store(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT r.company_id, r.credit_limit FROM score_runs r
+             JOIN score_runs cur ON cur.company_id = r.company_id AND cur.import_id = ?1
+             WHERE r.id < cur.id ORDER BY r.id",
+        )?;
+        let limits = stmt
+            .query_map([import_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(limits)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_version(conn: &Connection) -> usize {
+        conn.pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn migrates_fresh_and_existing_databases() {
+        let mut fresh = Connection::open_in_memory().unwrap();
+        migrate(&mut fresh).unwrap();
+        migrate(&mut fresh).unwrap();
+        assert_eq!(user_version(&fresh), MIGRATIONS.len());
+
+        // 第 1 版数据库中的申请在迁移后保留,尚未关联评分记录
+        let mut existing = Connection::open_in_memory().unwrap();
+        existing.execute_batch(MIGRATIONS[0]).unwrap();
+        existing.pragma_update(None, "user_version", 1).unwrap();
+        existing
+            .execute(
+                "INSERT INTO bank_submissions (company_id, company_name, credit_score,
+                     credit_rating, credit_limit, risk_level, submitted_at)
+                 VALUES ('C1', '甲', 80.0, 'AA', '500-800万', '低', 1)",
+                [],
+            )
+            .unwrap();
+        migrate(&mut existing).unwrap();
+        assert_eq!(user_version(&existing), MIGRATIONS.len());
+        let run: Option<i64> = existing
+            .query_row(
+                "SELECT score_run_id FROM bank_submissions WHERE company_id = 'C1'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(run, None);
+    }
+
+    #[test]
+    fn rejects_databases_from_newer_versions() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", MIGRATIONS.len() + 1)
+            .unwrap();
+        assert!(migrate(&mut conn).is_err());
+    }
+}
@@ -0,0 +1,382 @@
+use crate::excel::batch::par_map;
+use crate::excel::calc::{rating_notches, score_company, suggested_exposure};
+use crate::excel::history::sync_latest_period;
+use crate::excel::scorecard::{find_scorecard, Scorecard};
+use crate::excel::types::{
+    CompanyData, CompanyDataEn, CompanyWithScore, ExcelResult, Shock, ShockKind, StressScenario,
+    StressScenarioResult, StressTestResult, StressedCompany,
+};
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use serde_json::{Map, Value};
+
+impl Shock {
+    fn applies_to(&self, industry: &str) -> bool {
+        self.industries.is_empty() || self.industries.iter().any(|i| i == industry)
+    }
+
+    /// 冲击后的字段值,缺失的选填字段保持缺失,计数类字段取整并限制在 0 到 i32::MAX 之间。
+    /// 比例冲击按绝对值计算,负值(如亏损)在下行冲击下继续恶化而不是收窄
+    fn apply(&self, current: &Value) -> Result<Value, String> {
+        let Value::Number(number) = current else {
+            return match current {
+                Value::Null => Ok(Value::Null),
+                _ => Err(format!("字段 {} 不是数值,无法施加冲击", self.field)),
+            };
+        };
+        let x = number.as_f64().unwrap_or_default();
+        let shocked = match self.kind {
+            ShockKind::Relative => x + x.abs() * self.value,
+            ShockKind::Absolute => x + self.value,
+        };
+        Ok(if number.is_f64() {
+            Value::from(shocked)
+        } else {
+            Value::from(shocked.round().clamp(0.0, f64::from(i32::MAX)) as i64)
+        })
+    }
+}
+
+fn company_fields(company: &CompanyData) -> Result<Map<String, Value>, String> {
+    match serde_json::to_value(CompanyDataEn::from(company.clone())) {
+        Ok(Value::Object(fields)) => Ok(fields),
+        Ok(_) => Err("企业数据格式错误".into()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// 施加情景中适用于该企业行业的冲击,没有适用的冲击时返回 None。
+/// 冲击作用于评分快照,并同步到同一年度的历史数据,收入增长率等趋势因子也随之变化
+fn apply_shocks(company: &CompanyData, shocks: &[Shock]) -> Result<Option<CompanyData>, String> {
+    let shocks: Vec<&Shock> = shocks
+        .iter()
+        .filter(|s| s.applies_to(&company.industry))
+        .collect();
+    if shocks.is_empty() {
+        return Ok(None);
+    }
+    let mut fields = company_fields(company)?;
+    for shock in shocks {
+        let slot = fields
+            .get_mut(&shock.field)
+            .ok_or_else(|| format!("未知字段: {}", shock.field))?;
+        *slot = shock.apply(slot)?;
+    }
+    let company: CompanyDataEn = serde_json::from_value(Value::Object(fields))
+        .map_err(|e| format!("冲击后的企业数据无效: {}", e))?;
+    let mut company = CompanyData::from(company);
+    sync_latest_period(&mut company);
+    Ok(Some(company))
+}
+
+/// 在导入数据上运行压力测试:按情景对企业数据施加冲击后,用导入时的评分卡重新评分,
+/// 与导入时的评分结果对比。冲击后的数据不再做一致性修正,以免抵消冲击
+pub fn run_stress_test(
+    import_id: i64,
+    results: &[ExcelResult],
+    scenarios: &[StressScenario],
+) -> Result<StressTestResult, String> {
+    let first = results.first().ok_or("导入记录没有评分结果")?;
+    let scorecard = find_scorecard(&first.scorecard.hash)?;
+    let companies: Vec<&CompanyWithScore> = results.iter().flat_map(|r| &r.companies).collect();
+    let scenarios = scenarios
+        .iter()
+        .map(|scenario| run_scenario(scenario, &companies, &scorecard))
+        .collect::<Result<_, _>>()?;
+    Ok(StressTestResult {
+        import_id,
+        scorecard: first.scorecard.clone(),
+        total_companies: companies.len(),
+        scenarios,
+    })
+}
+
+fn run_scenario(
+    scenario: &StressScenario,
+    companies: &[&CompanyWithScore],
+    scorecard: &Scorecard,
+) -> Result<StressScenarioResult, String> {
+    if scenario.name.trim().is_empty() {
+        return Err("压力情景名称不能为空".into());
+    }
+    if let Some(shock) = scenario.shocks.iter().find(|s| !s.value.is_finite()) {
+        return Err(format!(
+            "情景 {} 中字段 {} 的冲击值无效",
+            scenario.name, shock.field
+        ));
+    }
+
+    let stressed = par_map(companies.to_vec(), |base| {
+        let Some(shocked) = apply_shocks(&base.company_data, &scenario.shocks)? else {
+            return Ok(None);
+        };
+        Ok::<_, String>(Some((base, score_company(shocked, scorecard))))
+    });
+
+    let mut result = StressScenarioResult {
+        name: scenario.name.clone(),
+        affected: 0,
+        downgraded: 0,
+        upgraded: 0,
+        newly_rejected: 0,
+        average_score_change: 0.0,
+        base_total_limit: companies
+            .iter()
+            .fold(0.0, |sum, c| sum + suggested_exposure(&c.credit_limit)),
+        stressed_total_limit: 0.0,
+        limit_change: 0.0,
+        limit_change_ratio: 0.0,
+        companies: Vec::new(),
+    };
+    let mut score_change = 0.0;
+    for outcome in stressed {
+        let Some((base, stressed)) = outcome? else {
+            continue;
+        };
+        result.affected += 1;
+        score_change += stressed.credit_score - base.credit_score;
+        let limit_change =
+            suggested_exposure(&stressed.credit_limit) - suggested_exposure(&base.credit_limit);
+        result.limit_change += limit_change;
+
+        let notches = rating_notches(&base.credit_rating, &stressed.credit_rating);
+        let newly_rejected = stressed.credit_limit == "拒绝授信" && base.credit_limit != "拒绝授信";
+        match notches {
+            n if n < 0 => result.downgraded += 1,
+            n if n > 0 => result.upgraded += 1,
+            _ => {}
+        }
+        if newly_rejected {
+            result.newly_rejected += 1;
+        }
+        if notches != 0 || limit_change != 0.0 {
+            result.companies.push(StressedCompany {
+                company_id: base.company_data.company_id.clone(),
+                company_name: base.company_data.company_name.clone(),
+                industry: base.company_data.industry.clone(),
+                base: base.into(),
+                stressed: (&stressed).into(),
+                notches,
+                newly_rejected,
+                limit_change,
+            });
+        }
+    }
+
+    if result.affected > 0 {
+        result.average_score_change = score_change / result.affected as f64;
+    }
+    result.stressed_total_limit = result.base_total_limit + result.limit_change;
+    if result.base_total_limit > 0.0 {
+        result.limit_change_ratio = result.limit_change / result.base_total_limit;
+    }
+    result.companies.sort_by(|a, b| {
+        a.notches
+            .cmp(&b.notches)
+            .then_with(|| a.limit_change.total_cmp(&b.limit_change))
+            .then_with(|| a.company_id.cmp(&b.company_id))
+    });
+    Ok(result)
+}
+
+/// 导出压力测试报告:情景汇总及各情景下评级或额度变化的企业
+pub fn write_stress_report(file_path: &str, result: &StressTestResult) -> Result<(), String> {
+    let mut workbook = Workbook::new();
+    write_summary(workbook.add_worksheet(), result).map_err(|e| e.to_string())?;
+    for (i, scenario) in result.scenarios.iter().enumerate() {
+        // 工作表名最长 31 个字符且不能重复,以序号区分
+        let name: String = format!("{}-{}", i + 1, scenario.name)
+            .chars()
+            .filter(|c| !"[]:*?/\\".contains(*c))
+            .take(31)
+            .collect();
+        write_scenario(workbook.add_worksheet(), &name, scenario).map_err(|e| e.to_string())?;
+    }
+    workbook.save(file_path).map_err(|e| e.to_string())
+}
+
+fn write_summary(worksheet: &mut Worksheet, result: &StressTestResult) -> Result<(), XlsxError> {
+    let header_format = Format::new().set_bold().set_font_size(14.0);
+    let key_format = Format::new().set_bold();
+    let amount_format = Format::new().set_num_format("#,##0.00");
+    let percent_format = Format::new().set_num_format("0.00%");
+    let score_format = Format::new().set_num_format("0.00");
+
+    worksheet.set_name("情景汇总")?;
+    worksheet.write_string_with_format(0, 0, "压力测试报告", &header_format)?;
+    worksheet.write_string_with_format(1, 0, "评分卡", &key_format)?;
+    worksheet.write_string(
+        1,
+        1,
+        format!("{} v{}", result.scorecard.name, result.scorecard.version),
+    )?;
+    worksheet.write_string_with_format(2, 0, "企业数", &key_format)?;
+    worksheet.write_number(2, 1, result.total_companies as f64)?;
+
+    let headers = [
+        "情景",
+        "受冲击企业数",
+        "评级下调",
+        "评级上调",
+        "新增拒绝授信",
+        "平均评分变化",
+        "基准授信总额(万元)",
+        "压力授信总额(万元)",
+        "授信总额变化(万元)",
+        "变化比例",
+    ];
+    for (col, header) in headers.iter().enumerate() {
+        worksheet.write_string_with_format(4, col as u16, *header, &key_format)?;
+        worksheet.set_column_width(col as u16, 18.0)?;
+    }
+    for (i, scenario) in result.scenarios.iter().enumerate() {
+        let row = i as u32 + 5;
+        worksheet.write_string(row, 0, &scenario.name)?;
+        worksheet.write_number(row, 1, scenario.affected as f64)?;
+        worksheet.write_number(row, 2, scenario.downgraded as f64)?;
+        worksheet.write_number(row, 3, scenario.upgraded as f64)?;
+        worksheet.write_number(row, 4, scenario.newly_rejected as f64)?;
+        worksheet.write_number_with_format(row, 5, scenario.average_score_change, &score_format)?;
+        worksheet.write_number_with_format(row, 6, scenario.base_total_limit, &amount_format)?;
+        worksheet.write_number_with_format(
+            row,
+            7,
+            scenario.stressed_total_limit,
+            &amount_format,
+        )?;
+        worksheet.write_number_with_format(row, 8, scenario.limit_change, &amount_format)?;
+        worksheet.write_number_with_format(row, 9, scenario.limit_change_ratio, &percent_format)?;
+    }
+    Ok(())
+}
+
+fn write_scenario(
+    worksheet: &mut Worksheet,
+    name: &str,
+    scenario: &StressScenarioResult,
+) -> Result<(), XlsxError> {
+    let key_format = Format::new().set_bold();
+    let amount_format = Format::new().set_num_format("#,##0.00");
+
+    worksheet.set_name(name)?;
+    let headers = [
+        "企业ID",
+        "企业名称",
+        "行业",
+        "基准评分",
+        "压力评分",
+        "基准评级",
+        "压力评级",
+        "变动档数",
+        "基准额度",
+        "压力额度",
+        "额度变化(万元)",
+        "新增拒绝授信",
+    ];
+    for (col, header) in headers.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &key_format)?;
+        worksheet.set_column_width(col as u16, 14.0)?;
+    }
+    for (i, company) in scenario.companies.iter().enumerate() {
+        let row = i as u32 + 1;
+        worksheet.write_string(row, 0, &company.company_id)?;
+        worksheet.write_string(row, 1, &company.company_name)?;
+        worksheet.write_string(row, 2, &company.industry)?;
+        worksheet.write_number(row, 3, company.base.credit_score)?;
+        worksheet.write_number(row, 4, company.stressed.credit_score)?;
+        worksheet.write_string(row, 5, &company.base.credit_rating)?;
+        worksheet.write_string(row, 6, &company.stressed.credit_rating)?;
+        worksheet.write_number(row, 7, company.notches)?;
+        worksheet.write_string(row, 8, &company.base.credit_limit)?;
+        worksheet.write_string(row, 9, &company.stressed.credit_limit)?;
+        worksheet.write_number_with_format(row, 10, company.limit_change, &amount_format)?;
+        worksheet.write_string(row, 11, if company.newly_rejected { "是" } else { "否" })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::excel::history::{debt_ratio_change, revenue_cagr};
+    use crate::excel::types::FinancialPeriod;
+
+    fn period(fiscal_year: i32, revenue: f64, debt_to_asset_ratio: f64) -> FinancialPeriod {
+        FinancialPeriod {
+            fiscal_year,
+            revenue,
+            net_profit: 100.0,
+            total_assets: 3000.0,
+            total_liabilities: 30.0 * debt_to_asset_ratio,
+            debt_to_asset_ratio,
+            r_and_d_ratio: 5.0,
+        }
+    }
+
+    #[test]
+    fn shocks_reach_trend_factors_through_latest_period() {
+        let mut company: CompanyData = serde_json::from_value(serde_json::json!({
+            "企业ID": "S1",
+            "企业名称": "甲",
+            "行业": "人工智能",
+            "营业收入(万元)": 1210.0,
+            "净利润(万元)": 100.0,
+            "资产总额(万元)": 3000.0,
+            "负债总额(万元)": 1200.0,
+            "资产负债率(%)": 40.0,
+            "研发投入占比(%)": 5.0,
+            "专利数量": 3,
+            "上游核心企业数量": 2,
+            "下游客户数量": 5,
+            "历史逾期次数": 0,
+            "法律诉讼次数": 0,
+            "年度": 2023
+        }))
+        .unwrap();
+        company.history = vec![period(2021, 1000.0, 30.0), period(2023, 1210.0, 40.0)];
+        let shocks = [
+            Shock {
+                field: "revenue".into(),
+                kind: ShockKind::Relative,
+                value: -0.5,
+                industries: Vec::new(),
+            },
+            Shock {
+                field: "debt_to_asset_ratio".into(),
+                kind: ShockKind::Absolute,
+                value: 10.0,
+                industries: Vec::new(),
+            },
+        ];
+
+        let shocked = apply_shocks(&company, &shocks).unwrap().unwrap();
+
+        assert_eq!(shocked.revenue, 605.0);
+        assert_eq!(shocked.history[0].revenue, 1000.0);
+        assert_eq!(shocked.history[1].revenue, 605.0);
+        assert!(revenue_cagr(&shocked).unwrap() < 0.0);
+        assert!(revenue_cagr(&company).unwrap() > 0.0);
+        assert_eq!(debt_ratio_change(&shocked), Some(20.0));
+    }
+
+    #[test]
+    fn relative_shocks_deepen_losses_and_clamp_counts() {
+        let shock = |field: &str, value: f64| Shock {
+            field: field.into(),
+            kind: ShockKind::Relative,
+            value,
+            industries: Vec::new(),
+        };
+
+        let loss = shock("net_profit", -0.4)
+            .apply(&Value::from(-100.0))
+            .unwrap();
+        let profit = shock("net_profit", -0.4)
+            .apply(&Value::from(100.0))
+            .unwrap();
+        let patents = shock("patent_count", 1e12).apply(&Value::from(3)).unwrap();
+
+        assert_eq!(loss.as_f64(), Some(-140.0));
+        assert_eq!(profit.as_f64(), Some(60.0));
+        assert_eq!(patents.as_i64(), Some(i64::from(i32::MAX)));
+    }
+}
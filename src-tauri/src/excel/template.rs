@@ -0,0 +1,358 @@
+use rust_xlsxwriter::{
+    DataValidation, DataValidationErrorStyle, DataValidationRule, Format, FormatAlign, Formula,
+    Note, Workbook, Worksheet, XlsxError,
+};
+
+/// 模板附带的说明工作表,导入时直接跳过
+pub const INSTRUCTIONS_SHEET: &str = "填写说明";
+/// 数据校验覆盖的最后一行(不含表头共 10000 行)
+const LAST_ROW: u32 = 10000;
+
+/// 模板字段的取值约束
+enum FieldRule {
+    Text,
+    /// 从评分卡行业列表中选择
+    Industry,
+    /// 任意数值,可为负数
+    Amount,
+    /// 非负数值
+    NonNegative,
+    /// 0-100 的百分比
+    Percent,
+    /// 非负整数
+    Count,
+    Year,
+}
+
+struct FieldSpec {
+    header: &'static str,
+    required: bool,
+    rule: FieldRule,
+    description: &'static str,
+}
+
+const FIELDS: &[FieldSpec] = &[
+    FieldSpec {
+        header: "企业ID",
+        required: true,
+        rule: FieldRule::Text,
+        description: "企业唯一编号,同一年度内不可重复",
+    },
+    FieldSpec {
+        header: "企业名称",
+        required: true,
+        rule: FieldRule::Text,
+        description: "企业全称",
+    },
+    FieldSpec {
+        header: "行业",
+        required: true,
+        rule: FieldRule::Industry,
+        description: "从下拉列表选择,未列出的行业按一般行业评分",
+    },
+    FieldSpec {
+        header: "营业收入(万元)",
+        required: true,
+        rule: FieldRule::Amount,
+        description: "会计年度营业收入",
+    },
+    FieldSpec {
+        header: "净利润(万元)",
+        required: true,
+        rule: FieldRule::Amount,
+        description: "会计年度净利润,亏损填负数",
+    },
+    FieldSpec {
+        header: "资产总额(万元)",
+        required: true,
+        rule: FieldRule::NonNegative,
+        description: "期末资产总额",
+    },
+    FieldSpec {
+        header: "负债总额(万元)",
+        required: true,
+        rule: FieldRule::NonNegative,
+        description: "期末负债总额",
+    },
+    FieldSpec {
+        header: "资产负债率(%)",
+        required: true,
+        rule: FieldRule::Percent,
+        description: "负债总额 / 资产总额,填写百分数,如 35 表示 35%",
+    },
+    FieldSpec {
+        header: "研发投入占比(%)",
+        required: true,
+        rule: FieldRule::Percent,
+        description: "研发投入 / 营业收入,填写百分数,如 8 表示 8%",
+    },
+    FieldSpec {
+        header: "专利数量",
+        required: true,
+        rule: FieldRule::Count,
+        description: "有效专利数量",
+    },
+    FieldSpec {
+        header: "上游核心企业数量",
+        required: true,
+        rule: FieldRule::Count,
+        description: "稳定合作的上游核心供应商数量",
+    },
+    FieldSpec {
+        header: "下游客户数量",
+        required: true,
+        rule: FieldRule::Count,
+        description: "稳定合作的下游客户数量",
+    },
+    FieldSpec {
+        header: "历史逾期次数",
+        required: true,
+        rule: FieldRule::Count,
+        description: "历史贷款或账款逾期次数",
+    },
+    FieldSpec {
+        header: "法律诉讼次数",
+        required: true,
+        rule: FieldRule::Count,
+        description: "作为被告的法律诉讼次数",
+    },
+    FieldSpec {
+        header: "营运资金(万元)",
+        required: false,
+        rule: FieldRule::Amount,
+        description: "流动资产 - 流动负债,用于财务困境模型",
+    },
+    FieldSpec {
+        header: "留存收益(万元)",
+        required: false,
+        rule: FieldRule::Amount,
+        description: "盈余公积与未分配利润之和",
+    },
+    FieldSpec {
+        header: "息税前利润(万元)",
+        required: false,
+        rule: FieldRule::Amount,
+        description: "利润总额加利息费用",
+    },
+    FieldSpec {
+        header: "流动资产(万元)",
+        required: false,
+        rule: FieldRule::NonNegative,
+        description: "期末流动资产",
+    },
+    FieldSpec {
+        header: "流动负债(万元)",
+        required: false,
+        rule: FieldRule::NonNegative,
+        description: "期末流动负债",
+    },
+    FieldSpec {
+        header: "经营活动现金流量(万元)",
+        required: false,
+        rule: FieldRule::Amount,
+        description: "经营活动产生的现金流量净额",
+    },
+    FieldSpec {
+        header: "年度",
+        required: false,
+        rule: FieldRule::Year,
+        description: "会计年度,如 2023;也可将工作表命名为 2023年",
+    },
+];
+
+impl FieldRule {
+    fn requirement(&self) -> &'static str {
+        match self {
+            FieldRule::Text => "文本",
+            FieldRule::Industry => "下拉选择",
+            FieldRule::Amount => "数值",
+            FieldRule::NonNegative => "非负数值",
+            FieldRule::Percent => "0-100 之间的数值",
+            FieldRule::Count => "非负整数",
+            FieldRule::Year => "1900-2100 之间的整数",
+        }
+    }
+
+    fn error_message(&self, header: &str) -> String {
+        match self {
+            FieldRule::Industry => format!("{} 不在可选行业中,将按一般行业评分", header),
+            rule => format!("{} 应为{}", header, rule.requirement()),
+        }
+    }
+
+    fn validation(&self, industries: &str) -> Option<DataValidation> {
+        let validation =
+            match self {
+                FieldRule::Text => return None,
+                FieldRule::Industry => DataValidation::new()
+                    .allow_list_formula(Formula::new(industries))
+                    .set_error_style(DataValidationErrorStyle::Warning),
+                FieldRule::Amount => DataValidation::new()
+                    .allow_decimal_number(DataValidationRule::Between(-1e12, 1e12)),
+                FieldRule::NonNegative => DataValidation::new()
+                    .allow_decimal_number(DataValidationRule::GreaterThanOrEqualTo(0.0)),
+                FieldRule::Percent => DataValidation::new()
+                    .allow_decimal_number(DataValidationRule::Between(0.0, 100.0)),
+                FieldRule::Count => DataValidation::new()
+                    .allow_whole_number(DataValidationRule::GreaterThanOrEqualTo(0)),
+                FieldRule::Year => DataValidation::new()
+                    .allow_whole_number(DataValidationRule::Between(1900, 2100)),
+            };
+        Some(validation)
+    }
+}
+
+fn field_spec(header: &str) -> Option<&'static FieldSpec> {
+    FIELDS.iter().find(|f| f.header == header)
+}
+
+impl FieldSpec {
+    /// 表头括号中的单位,如 万元、%
+    fn unit(&self) -> Option<&'static str> {
+        let (_, unit) = self.header.split_once('(')?;
+        unit.strip_suffix(')')
+    }
+}
+
+/// 生成导入模板:表头批注说明字段含义与单位,数据区按字段添加下拉列表和数值校验,
+/// 另附填写说明工作表。industries 为评分卡中识别的行业
+pub fn write_template(
+    file_path: &str,
+    headers: &[String],
+    template_row: Option<&[String]>,
+    industries: &[String],
+) -> Result<(), String> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    write_data_sheet(worksheet, headers, template_row, industries.len())
+        .map_err(|e| e.to_string())?;
+    let instructions = workbook.add_worksheet();
+    write_instructions(instructions, headers, industries).map_err(|e| e.to_string())?;
+    workbook.save(file_path).map_err(|e| e.to_string())
+}
+
+fn write_data_sheet(
+    worksheet: &mut Worksheet,
+    headers: &[String],
+    template_row: Option<&[String]>,
+    industry_count: usize,
+) -> Result<(), XlsxError> {
+    let center_format = Format::new()
+        .set_align(FormatAlign::Center)
+        .set_align(FormatAlign::VerticalCenter);
+    let header_format = center_format.clone().set_bold();
+
+    // 行业下拉列表引用填写说明中的行业列,避免超出 Excel 列表长度限制
+    let industries = format!(
+        "='{}'!$G$2:$G${}",
+        INSTRUCTIONS_SHEET,
+        industry_count.max(1) + 1
+    );
+
+    for (col, header) in headers.iter().enumerate() {
+        let col = col as u16;
+        worksheet.write_string_with_format(0, col, header, &header_format)?;
+        worksheet.set_column_width(col, 15.0)?;
+
+        let Some(spec) = field_spec(header) else {
+            continue;
+        };
+        let mut note = format!(
+            "{},{}",
+            if spec.required { "必填" } else { "选填" },
+            spec.rule.requirement()
+        );
+        if let Some(unit) = spec.unit() {
+            note.push_str(&format!(",单位:{}", unit));
+        }
+        note.push('\n');
+        note.push_str(spec.description);
+        worksheet.insert_note(0, col, &Note::new(note).set_author("ChainCredit"))?;
+
+        if industry_count == 0 && matches!(spec.rule, FieldRule::Industry) {
+            continue;
+        }
+        if let Some(validation) = spec.rule.validation(&industries) {
+            let validation = validation
+                .set_input_title(header.as_str())?
+                .set_input_message(spec.rule.requirement())?
+                .set_error_title("输入不符合要求")?
+                .set_error_message(spec.rule.error_message(header))?;
+            worksheet.add_data_validation(1, col, LAST_ROW, col, &validation)?;
+        }
+    }
+    worksheet.set_freeze_panes(1, 0)?;
+
+    if let Some(template_data) = template_row {
+        for (col, value) in template_data.iter().take(headers.len()).enumerate() {
+            if let Ok(num) = value.parse::<f64>() {
+                worksheet.write_number_with_format(1, col as u16, num, &center_format)?;
+            } else {
+                worksheet.write_string_with_format(1, col as u16, value, &center_format)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_instructions(
+    worksheet: &mut Worksheet,
+    headers: &[String],
+    industries: &[String],
+) -> Result<(), XlsxError> {
+    let title_format = Format::new().set_bold().set_font_size(14.0);
+    let key_format = Format::new().set_bold();
+    let wrap_format = Format::new().set_text_wrap();
+
+    worksheet.set_name(INSTRUCTIONS_SHEET)?;
+    for (col, width) in [
+        (0, 24.0),
+        (1, 10.0),
+        (2, 10.0),
+        (3, 20.0),
+        (4, 50.0),
+        (6, 16.0),
+    ] {
+        worksheet.set_column_width(col, width)?;
+    }
+
+    let columns = ["字段", "是否必填", "单位", "取值要求", "说明"];
+    for (col, title) in columns.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *title, &key_format)?;
+    }
+    let mut row = 1;
+    for header in headers {
+        let Some(spec) = field_spec(header) else {
+            continue;
+        };
+        worksheet.write_string(row, 0, header)?;
+        worksheet.write_string(row, 1, if spec.required { "必填" } else { "选填" })?;
+        worksheet.write_string(row, 2, spec.unit().unwrap_or(""))?;
+        worksheet.write_string(row, 3, spec.rule.requirement())?;
+        worksheet.write_string_with_format(row, 4, spec.description, &wrap_format)?;
+        row += 1;
+    }
+    row += 1;
+
+    let notes = [
+        "每行填写一家企业,第一行为表头,请勿修改表头名称。",
+        "金额默认单位为万元;表头写明单位(如 营业收入(元))时导入会自动换算。",
+        "百分比填写百分数,如 35 表示 35%;也可直接填写 35%;填写小数时请将单元格设为百分比格式,或在表头标注(小数),如 资产负债率(小数)。",
+        "多个年度的数据可分别放在以年度命名的工作表中(如 2022年、2023年),评分使用最新年度。",
+        "本说明工作表不含企业ID和企业名称列,导入时会自动跳过。",
+    ];
+    worksheet.write_string_with_format(row, 0, "注意事项", &title_format)?;
+    row += 1;
+    for note in notes {
+        worksheet.write_string(row, 0, note)?;
+        row += 1;
+    }
+
+    worksheet.write_string_with_format(0, 6, "可选行业", &key_format)?;
+    for (i, industry) in industries.iter().enumerate() {
+        worksheet.write_string(i as u32 + 1, 6, industry)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use crate::excel::calc::{rating_notches, rating_rank, RATING_GRADES};
+use crate::excel::store::{company_runs, ratings_until};
+use crate::excel::types::{
+    CompanyData, CompanyWithScore, InputChange, RatingChange, RatingMigration, ScoreHistoryEntry,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 不参与输入对比的字段:历史财务随年度合并变化,原始单位不影响评分
+const IGNORED_INPUTS: [&str; 2] = ["历史财务", "原始单位"];
+
+/// 企业的评分时间线,按评分时间先后排列,每次评分附带相对上一次的评级变动和输入变化
+pub fn score_history(company_id: &str) -> Result<Vec<ScoreHistoryEntry>, String> {
+    let mut entries = Vec::new();
+    let mut previous: Option<CompanyWithScore> = None;
+    for run in company_runs(company_id)? {
+        let company: CompanyWithScore =
+            serde_json::from_str(&run.result).map_err(|e| format!("解析评分记录失败: {}", e))?;
+        let (previous_rating, rating_change, score_change, changed_inputs) = match &previous {
+            Some(prev) => (
+                Some(prev.credit_rating.clone()),
+                rating_notches(&prev.credit_rating, &company.credit_rating),
+                Some(company.credit_score - prev.credit_score),
+                changed_inputs(&prev.company_data, &company.company_data)?,
+            ),
+            None => (None, 0, None, Vec::new()),
+        };
+        entries.push(ScoreHistoryEntry {
+            import_id: run.import_id,
+            scored_at: run.scored_at,
+            snapshot: (&company).into(),
+            probability_of_default: company.probability_of_default,
+            scorecard: company.scorecard.clone(),
+            provenance: company.provenance.clone(),
+            previous_rating,
+            rating_change,
+            score_change,
+            changed_inputs,
+        });
+        previous = Some(company);
+    }
+    Ok(entries)
+}
+
+fn changed_inputs(before: &CompanyData, after: &CompanyData) -> Result<Vec<InputChange>, String> {
+    let to_fields = |company: &CompanyData| match serde_json::to_value(company) {
+        Ok(Value::Object(fields)) => Ok(fields),
+        Ok(_) => Err("企业数据格式错误".to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let before = to_fields(before)?;
+    let after = to_fields(after)?;
+    Ok(after
+        .into_iter()
+        .filter(|(field, _)| !IGNORED_INPUTS.contains(&field.as_str()))
+        .filter_map(|(field, after)| {
+            let before = before.get(&field).cloned().unwrap_or(Value::Null);
+            (before != after).then_some(InputChange {
+                field,
+                before,
+                after,
+            })
+        })
+        .collect())
+}
+
+/// 统计 from 与 to 两个时点之间的评级迁移
+pub fn rating_migration(from: i64, to: i64) -> Result<RatingMigration, String> {
+    if from > to {
+        return Err("起始时间不能晚于结束时间".into());
+    }
+
+    // 各企业在两个时点的最近一次评级
+    let mut start: HashMap<String, String> = HashMap::new();
+    let mut end: HashMap<String, (String, String)> = HashMap::new();
+    for row in ratings_until(to)? {
+        if row.scored_at <= from {
+            start.insert(row.company_id.clone(), row.credit_rating.clone());
+        }
+        end.insert(row.company_id, (row.company_name, row.credit_rating));
+    }
+
+    let grades: Vec<String> = RATING_GRADES.iter().rev().map(|g| g.to_string()).collect();
+    let index = |rating: &str| rating_rank(rating).map(|rank| RATING_GRADES.len() - 1 - rank);
+    let mut migration = RatingMigration {
+        from,
+        to,
+        matrix: vec![vec![0; grades.len()]; grades.len()],
+        grades,
+        upgraded: 0,
+        downgraded: 0,
+        unchanged: 0,
+        new_companies: 0,
+        changes: Vec::new(),
+    };
+    for (company_id, (company_name, to_rating)) in end {
+        let Some(from_rating) = start.remove(&company_id) else {
+            migration.new_companies += 1;
+            continue;
+        };
+        let (Some(i), Some(j)) = (index(&from_rating), index(&to_rating)) else {
+            continue;
+        };
+        migration.matrix[i][j] += 1;
+        let change = rating_notches(&from_rating, &to_rating);
+        match change {
+            0 => migration.unchanged += 1,
+            c if c > 0 => migration.upgraded += 1,
+            _ => migration.downgraded += 1,
+        }
+        if change != 0 {
+            migration.changes.push(RatingChange {
+                company_id,
+                company_name,
+                from_rating,
+                to_rating,
+                notches: change,
+            });
+        }
+    }
+    migration.changes.sort_by(|a, b| {
+        a.notches
+            .cmp(&b.notches)
+            .then_with(|| a.company_id.cmp(&b.company_id))
+    });
+    Ok(migration)
+}
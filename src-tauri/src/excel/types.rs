@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct CompanyData {
     #[serde(rename = "企业ID")]
@@ -29,6 +30,50 @@ pub struct CompanyData {
     pub overdue_count: i32,
     #[serde(rename = "法律诉讼次数")]
     pub legal_disputes_count: i32,
+    #[serde(rename = "营运资金(万元)", default)]
+    pub working_capital: Option<f64>,
+    #[serde(rename = "留存收益(万元)", default)]
+    pub retained_earnings: Option<f64>,
+    #[serde(rename = "息税前利润(万元)", default)]
+    pub ebit: Option<f64>,
+    #[serde(rename = "流动资产(万元)", default)]
+    pub current_assets: Option<f64>,
+    #[serde(rename = "流动负债(万元)", default)]
+    pub current_liabilities: Option<f64>,
+    #[serde(rename = "经营活动现金流量(万元)", default)]
+    pub operating_cash_flow: Option<f64>,
+    #[serde(rename = "年度", default)]
+    pub fiscal_year: Option<i32>,
+    #[serde(rename = "历史财务", default)]
+    pub history: Vec<FinancialPeriod>,
+    /// 换算前的金额单位,仅记录非万元的列
+    #[serde(rename = "原始单位", default)]
+    pub original_units: BTreeMap<String, Unit>,
+}
+
+/// 金额单位,内部统一换算为万元
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Unit {
+    #[serde(rename = "元")]
+    Yuan,
+    #[serde(rename = "千元")]
+    Thousand,
+    #[serde(rename = "万元")]
+    TenThousand,
+    #[serde(rename = "亿元")]
+    HundredMillion,
+}
+
+/// 单个会计年度的财务数据
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct FinancialPeriod {
+    pub fiscal_year: i32,
+    pub revenue: f64,
+    pub net_profit: f64,
+    pub total_assets: f64,
+    pub total_liabilities: f64,
+    pub debt_to_asset_ratio: f64,
+    pub r_and_d_ratio: f64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -40,6 +85,72 @@ pub struct CompanyWithScore {
     pub credit_limit: String,
     pub risk_level: String,
     pub score_details: ScoreDetails,
+    pub factor_details: Vec<FactorContribution>,
+    pub reason_codes: Vec<ReasonCode>,
+    pub scorecard: ScorecardId,
+    pub probability_of_default: Option<f64>,
+    pub distress: DistressIndicators,
+    pub warnings: Vec<ConsistencyWarning>,
+    pub provenance: Option<Provenance>,
+}
+
+/// 财务困境模型指标,所需字段缺失时对应项为空
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DistressIndicators {
+    pub altman_z: Option<f64>,
+    pub altman_zone: Option<String>,
+    pub ohlson_o: Option<f64>,
+    pub ohlson_probability: Option<f64>,
+    pub zmijewski: Option<f64>,
+    pub zmijewski_probability: Option<f64>,
+}
+
+/// 跨字段一致性检查提示,corrected 表示已按推算值修正
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConsistencyWarning {
+    pub rule: String,
+    pub field: String,
+    pub message: String,
+    pub original: f64,
+    pub expected: f64,
+    pub corrected: bool,
+}
+
+/// 评分卡标识,用于追溯评分结果所使用的规则
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScorecardId {
+    pub name: String,
+    pub version: String,
+    pub hash: String,
+}
+
+/// 单个评分因子的得分明细
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FactorContribution {
+    pub key: String,
+    pub name: String,
+    pub category: String,
+    pub value: FactorValue,
+    pub band: String,
+    pub points: f64,
+    pub max_points: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum FactorValue {
+    Number(f64),
+    Text(String),
+    Missing,
+}
+
+/// 扣分理由,按失分从多到少排列
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReasonCode {
+    pub code: String,
+    pub factor: String,
+    pub message: String,
+    pub points_lost: f64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -49,6 +160,135 @@ pub struct ScoreDetails {
     pub supply_chain_score: f64,
     pub risk_score: f64,
     pub industry_adjustment: f64,
+    #[serde(default)]
+    pub trend_adjustment: f64,
+}
+
+/// 评分结果摘要
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScoreSnapshot {
+    pub credit_score: f64,
+    pub credit_rating: String,
+    pub credit_limit: String,
+    pub risk_level: String,
+}
+
+/// 模拟调整前后的评分对比
+#[derive(Serialize, Debug, Clone)]
+pub struct WhatIfResult {
+    pub original: ScoreSnapshot,
+    pub simulated: ScoreSnapshot,
+    pub score_change: f64,
+    pub company_data: CompanyDataEn,
+}
+
+/// 单一字段调整建议
+#[derive(Serialize, Debug, Clone)]
+pub struct SensitivityItem {
+    pub field: String,
+    pub field_name: String,
+    pub current_value: f64,
+    pub required_value: f64,
+    pub change: f64,
+    pub relative_change: f64,
+    pub new_score: f64,
+    pub new_rating: String,
+}
+
+/// 提升一个评级所需的最小调整,已是最高评级时 target_rating 为空
+#[derive(Serialize, Debug, Clone)]
+pub struct SensitivityResult {
+    pub current_score: f64,
+    pub current_rating: String,
+    pub target_rating: Option<String>,
+    pub suggestions: Vec<SensitivityItem>,
+}
+
+/// 违约概率分组校准结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CalibrationBucket {
+    pub count: usize,
+    pub defaults: usize,
+    pub mean_pd: f64,
+    pub observed_default_rate: f64,
+    pub min_pd: f64,
+    pub max_pd: f64,
+}
+
+/// 违约概率模型校准统计,hosmer_lemeshow 为 Hosmer-Lemeshow 卡方统计量,
+/// sample_count 等为参与统计的样本
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PdCalibration {
+    pub sample_count: usize,
+    pub default_count: usize,
+    pub observed_default_rate: f64,
+    pub mean_pd: f64,
+    pub brier_score: f64,
+    pub log_loss: f64,
+    pub hosmer_lemeshow: f64,
+    pub hosmer_lemeshow_dof: usize,
+    pub buckets: Vec<CalibrationBucket>,
+    /// 统计基于拟合时未使用的留出样本;为 false 时是训练样本内的结果,会偏乐观
+    #[serde(default)]
+    pub held_out: bool,
+    pub trained_at: i64,
+}
+
+/// 各评级的实际违约情况
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GradeDefaultRate {
+    pub rating: String,
+    pub count: usize,
+    pub defaults: usize,
+    pub default_rate: f64,
+}
+
+/// 评分低于截断值视为预测违约
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfusionMatrix {
+    pub cutoff: f64,
+    pub true_positive: usize,
+    pub false_positive: usize,
+    pub true_negative: usize,
+    pub false_negative: usize,
+    pub accuracy: f64,
+    pub precision: f64,
+    pub recall: f64,
+    pub false_positive_rate: f64,
+}
+
+/// 评分卡回测结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BacktestResult {
+    pub file: String,
+    pub scorecard: ScorecardId,
+    pub sample_count: usize,
+    pub default_count: usize,
+    pub auc: f64,
+    pub gini: f64,
+    pub ks: f64,
+    pub ks_score: f64,
+    pub grade_default_rates: Vec<GradeDefaultRate>,
+    pub confusion_matrix: ConfusionMatrix,
+}
+
+/// 单项指标在同业中的百分位排名,peer_count 为同业企业数(不含自身)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerPercentile {
+    pub metric: String,
+    pub metric_name: String,
+    pub value: f64,
+    pub percentile: f64,
+    pub peer_median: f64,
+}
+
+/// 企业同业对标结果,无同业企业时 percentiles 为空
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerStats {
+    pub company_id: String,
+    pub industry: String,
+    pub peer_count: usize,
+    pub percentiles: Vec<PeerPercentile>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -56,7 +296,9 @@ pub struct ExcelResult {
     pub file: String,
     pub sheet_name: String,
     pub total_companies: usize,
+    pub scorecard: ScorecardId,
     pub companies: Vec<CompanyWithScore>,
+    pub peer_stats: Vec<PeerStats>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -75,6 +317,24 @@ pub struct CompanyDataEn {
     pub downstream_customers: i32,
     pub overdue_count: i32,
     pub legal_disputes_count: i32,
+    #[serde(default)]
+    pub working_capital: Option<f64>,
+    #[serde(default)]
+    pub retained_earnings: Option<f64>,
+    #[serde(default)]
+    pub ebit: Option<f64>,
+    #[serde(default)]
+    pub current_assets: Option<f64>,
+    #[serde(default)]
+    pub current_liabilities: Option<f64>,
+    #[serde(default)]
+    pub operating_cash_flow: Option<f64>,
+    #[serde(default)]
+    pub fiscal_year: Option<i32>,
+    #[serde(default)]
+    pub history: Vec<FinancialPeriod>,
+    #[serde(default)]
+    pub original_units: BTreeMap<String, Unit>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -84,6 +344,8 @@ pub struct ScoreDetailsEn {
     pub supply_chain_score: f64,
     pub risk_score: f64,
     pub industry_adjustment: f64,
+    #[serde(default)]
+    pub trend_adjustment: f64,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -94,6 +356,81 @@ pub struct CompanyWithScoreEn {
     pub credit_limit: String,
     pub risk_level: String,
     pub score_details: ScoreDetailsEn,
+    pub factor_details: Vec<FactorContribution>,
+    pub reason_codes: Vec<ReasonCode>,
+    pub scorecard: ScorecardId,
+    pub probability_of_default: Option<f64>,
+    pub distress: DistressIndicators,
+    pub warnings: Vec<ConsistencyWarning>,
+    pub provenance: Option<Provenance>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// 企业数据在源文件中的位置,row 从 1 开始
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub sheet_name: String,
+    pub row: usize,
+}
+
+/// 评分结果的数据来源,可据此找回评分所用的原始输入
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    pub file: String,
+    /// 源文件内容的 SHA-256 十六进制摘要
+    pub file_hash: String,
+    pub sheet_name: String,
+    /// 工作表中的行号,从 1 开始,含表头
+    pub row: usize,
+    /// 导入时间,Unix 时间戳(秒)
+    pub imported_at: i64,
+}
+
+/// 批量导入所处阶段
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStage {
+    Reading,
+    Scoring,
+}
+
+/// 批量导入进度,通过 import_progress 事件推送。
+/// 读取阶段 files_done 为已读完的文件数,评分阶段为已全部评分的文件数
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImportProgress {
+    pub stage: ImportStage,
+    pub files_done: usize,
+    pub files_total: usize,
+    pub rows_read: usize,
+    pub rows_scored: usize,
+    pub rows_total: usize,
+}
+
+/// 导入校验诊断信息,row 为工作表中的行号(从 1 开始,含表头)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Diagnostic {
+    pub file: String,
+    pub sheet_name: String,
+    pub row: usize,
+    pub column: Option<String>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// 导入结果,存在错误的行不参与评分,仅在 diagnostics 中报告
+#[derive(Serialize)]
+pub struct ImportResult {
+    /// 本地数据库中的导入记录 ID,没有评分结果时为空
+    pub import_id: Option<i64>,
+    pub results: Vec<ExcelResultEn>,
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 #[derive(Serialize)]
@@ -101,7 +438,146 @@ pub struct ExcelResultEn {
     pub file: String,
     pub sheet_name: String,
     pub total_companies: usize,
+    pub scorecard: ScorecardId,
     pub companies: Vec<CompanyWithScoreEn>,
+    pub peer_stats: Vec<PeerStats>,
+}
+
+/// 表头识别方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchSource {
+    /// 与标准列名一致
+    Exact,
+    /// 命中内置别名
+    Alias,
+    /// 命中用户自定义映射
+    User,
+    /// 与前面的列映射到同一标准列,已忽略
+    Duplicate,
+    /// 未识别
+    Unmatched,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    pub index: usize,
+    pub header: String,
+    pub field: Option<String>,
+    pub matched_by: MatchSource,
+    /// 金额列的单位,非金额列为空
+    pub unit: Option<Unit>,
+}
+
+/// 单个数据来源的自定义映射,键为原始列名
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SourceMapping {
+    /// 原始列名 -> 标准列名
+    #[serde(default)]
+    pub columns: HashMap<String, String>,
+    /// 原始列名 -> 声明的金额单位
+    #[serde(default)]
+    pub units: HashMap<String, Unit>,
+}
+
+/// 单个工作表的表头映射预览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SheetMapping {
+    pub file: String,
+    pub sheet_name: String,
+    pub columns: Vec<ColumnMapping>,
+    pub missing: Vec<String>,
+}
+
+/// 本地数据库中的一次导入记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRecord {
+    pub id: i64,
+    /// 导入时间,Unix 时间戳(秒)
+    pub imported_at: i64,
+    pub source: Option<String>,
+    pub scorecard: ScorecardId,
+    pub files: Vec<String>,
+    pub total_companies: usize,
+}
+
+/// 提交给银行的授信申请
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BankSubmission {
+    pub company_id: String,
+    pub company_name: String,
+    pub credit_score: f64,
+    pub credit_rating: String,
+    pub credit_limit: String,
+    pub risk_level: String,
+}
+
+/// 授信申请及其批复结果,尚未批复时 approved_limit 为空
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BankRecord {
+    pub id: i64,
+    #[serde(flatten)]
+    pub submission: BankSubmission,
+    pub submitted_at: i64,
+    /// 批复额度(万元)
+    pub approved_limit: Option<f64>,
+    pub approved_at: Option<i64>,
+}
+
+/// 与上一次评分相比发生变化的输入字段,field 为中文列名
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputChange {
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+/// 企业评分时间线中的一次评分
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreHistoryEntry {
+    pub import_id: i64,
+    /// 评分时间,Unix 时间戳(秒)
+    pub scored_at: i64,
+    #[serde(flatten)]
+    pub snapshot: ScoreSnapshot,
+    pub probability_of_default: Option<f64>,
+    pub scorecard: ScorecardId,
+    pub provenance: Option<Provenance>,
+    /// 上一次评分的评级,首次评分时为空
+    pub previous_rating: Option<String>,
+    /// 评级变动档数,正数为上调
+    pub rating_change: i32,
+    pub score_change: Option<f64>,
+    pub changed_inputs: Vec<InputChange>,
+}
+
+/// 单家企业在两个时点之间的评级变动
+#[derive(Debug, Clone, Serialize)]
+pub struct RatingChange {
+    pub company_id: String,
+    pub company_name: String,
+    pub from_rating: String,
+    pub to_rating: String,
+    /// 变动档数,正数为上调
+    pub notches: i32,
+}
+
+/// 两个时点之间的评级迁移矩阵,各时点取企业在该时点及之前的最近一次评分
+#[derive(Debug, Clone, Serialize)]
+pub struct RatingMigration {
+    pub from: i64,
+    pub to: i64,
+    /// 评级由高到低排列
+    pub grades: Vec<String>,
+    /// matrix[i][j] 为期初评级 grades[i]、期末评级 grades[j] 的企业数
+    pub matrix: Vec<Vec<usize>>,
+    pub upgraded: usize,
+    pub downgraded: usize,
+    pub unchanged: usize,
+    /// 期初尚无评分的企业数,不计入矩阵
+    pub new_companies: usize,
+    /// 评级发生变动的企业,下调幅度大的在前
+    pub changes: Vec<RatingChange>,
 }
 
 impl From<CompanyData> for CompanyDataEn {
@@ -121,6 +597,45 @@ impl From<CompanyData> for CompanyDataEn {
             downstream_customers: c.downstream_customers,
             overdue_count: c.overdue_count,
             legal_disputes_count: c.legal_disputes_count,
+            working_capital: c.working_capital,
+            retained_earnings: c.retained_earnings,
+            ebit: c.ebit,
+            current_assets: c.current_assets,
+            current_liabilities: c.current_liabilities,
+            operating_cash_flow: c.operating_cash_flow,
+            fiscal_year: c.fiscal_year,
+            history: c.history,
+            original_units: c.original_units,
+        }
+    }
+}
+
+impl From<CompanyDataEn> for CompanyData {
+    fn from(c: CompanyDataEn) -> Self {
+        Self {
+            company_id: c.company_id,
+            company_name: c.company_name,
+            industry: c.industry,
+            revenue: c.revenue,
+            net_profit: c.net_profit,
+            total_assets: c.total_assets,
+            total_liabilities: c.total_liabilities,
+            debt_to_asset_ratio: c.debt_to_asset_ratio,
+            r_and_d_ratio: c.r_and_d_ratio,
+            patent_count: c.patent_count,
+            upstream_core_companies: c.upstream_core_companies,
+            downstream_customers: c.downstream_customers,
+            overdue_count: c.overdue_count,
+            legal_disputes_count: c.legal_disputes_count,
+            working_capital: c.working_capital,
+            retained_earnings: c.retained_earnings,
+            ebit: c.ebit,
+            current_assets: c.current_assets,
+            current_liabilities: c.current_liabilities,
+            operating_cash_flow: c.operating_cash_flow,
+            fiscal_year: c.fiscal_year,
+            history: c.history,
+            original_units: c.original_units,
         }
     }
 }
@@ -133,6 +648,7 @@ impl From<ScoreDetails> for ScoreDetailsEn {
             supply_chain_score: s.supply_chain_score,
             risk_score: s.risk_score,
             industry_adjustment: s.industry_adjustment,
+            trend_adjustment: s.trend_adjustment,
         }
     }
 }
@@ -146,6 +662,13 @@ impl From<CompanyWithScore> for CompanyWithScoreEn {
             credit_limit: c.credit_limit,
             risk_level: c.risk_level,
             score_details: c.score_details.into(),
+            factor_details: c.factor_details,
+            reason_codes: c.reason_codes,
+            scorecard: c.scorecard,
+            probability_of_default: c.probability_of_default,
+            distress: c.distress,
+            warnings: c.warnings,
+            provenance: c.provenance,
         }
     }
 }
@@ -156,7 +679,211 @@ impl From<ExcelResult> for ExcelResultEn {
             file: e.file,
             sheet_name: e.sheet_name,
             total_companies: e.total_companies,
+            scorecard: e.scorecard,
             companies: e.companies.into_iter().map(|c| c.into()).collect(),
+            peer_stats: e.peer_stats,
         }
     }
 }
+
+/// 组合中按评级、行业或风险等级划分的一组企业
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExposureBucket {
+    pub name: String,
+    pub count: usize,
+    /// 建议授信敞口(万元)
+    pub exposure: f64,
+    /// 敞口占组合总敞口的比例
+    pub exposure_share: f64,
+    pub average_score: f64,
+}
+
+/// 组合中的单家企业
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PortfolioCompany {
+    pub company_id: String,
+    pub company_name: String,
+    pub industry: String,
+    pub credit_score: f64,
+    pub credit_rating: String,
+    pub risk_level: String,
+    pub exposure: f64,
+    pub probability_of_default: Option<f64>,
+}
+
+/// 一次导入的组合分析结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PortfolioSummary {
+    pub import_id: i64,
+    pub total_companies: usize,
+    /// 建议授信总敞口(万元),需要担保和拒绝授信的企业不计敞口
+    pub total_exposure: f64,
+    pub average_score: f64,
+    /// 按敞口加权的平均评分,总敞口为 0 时等于平均评分
+    pub weighted_average_score: f64,
+    /// 按评级由高到低排列,包含没有企业的评级
+    pub rating_distribution: Vec<ExposureBucket>,
+    /// 按敞口由大到小排列
+    pub industry_exposure: Vec<ExposureBucket>,
+    pub risk_level_exposure: Vec<ExposureBucket>,
+    /// 行业敞口集中度 HHI,取值 0-10000,越大越集中
+    pub industry_hhi: f64,
+    /// 敞口最大的企业
+    pub top_exposures: Vec<PortfolioCompany>,
+    /// 评分最低的企业
+    pub top_risks: Vec<PortfolioCompany>,
+}
+
+/// 预期信用损失使用的违约概率来源
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PdSource {
+    /// 按评级查表
+    Table,
+    /// 使用违约概率模型,尚未拟合模型的企业按评级查表
+    Model,
+}
+
+/// 违约风险敞口来源
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EadSource {
+    /// 银行批复额度
+    Approved,
+    /// 尚未批复,使用建议授信额度
+    Suggested,
+    /// 当前评级不给予授信,使用此前评分中最近一次的建议授信额度
+    Previous,
+    /// 没有批复,也没有可用的授信额度,风险敞口未知
+    Missing,
+}
+
+/// 单家企业的预期信用损失,金额单位为万元
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompanyEcl {
+    pub company_id: String,
+    pub company_name: String,
+    pub credit_rating: String,
+    /// 首次评分时的评级
+    pub initial_rating: Option<String>,
+    /// 减值阶段 1/2/3
+    pub stage: u8,
+    /// 划入阶段 2、3 的原因
+    pub stage_reason: Option<String>,
+    pub collateral_type: String,
+    pub pd_12m: f64,
+    /// 违约概率的实际来源;阶段 3 的企业视为已违约,违约概率为 1,来源为空
+    pub pd_source: Option<PdSource>,
+    pub pd_lifetime: f64,
+    pub lgd: f64,
+    /// 风险敞口,来源为 Missing 时为空,各项 ECL 也为空
+    pub ead: Option<f64>,
+    pub ead_source: EadSource,
+    pub ecl_12m: Option<f64>,
+    pub ecl_lifetime: Option<f64>,
+    /// 计提金额:阶段 1 为 12 个月 ECL,阶段 2、3 为整个存续期 ECL
+    pub ecl: Option<f64>,
+}
+
+/// 单个减值阶段的汇总
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StageTotal {
+    pub stage: u8,
+    pub count: usize,
+    pub ead: f64,
+    pub ecl: f64,
+}
+
+/// 一次导入的预期信用损失计算结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EclResult {
+    pub import_id: i64,
+    /// 参数中配置的违约概率来源,各企业实际使用的来源见 CompanyEcl
+    pub pd_source: PdSource,
+    pub lifetime_years: f64,
+    pub total_ead: f64,
+    pub total_ecl_12m: f64,
+    pub total_ecl_lifetime: f64,
+    /// 按阶段计提的预期信用损失合计
+    pub total_ecl: f64,
+    /// 拨备覆盖率 total_ecl / total_ead
+    pub coverage_ratio: f64,
+    /// 风险敞口未知、未计入合计的企业数
+    pub missing_ead: usize,
+    pub stages: Vec<StageTotal>,
+    pub companies: Vec<CompanyEcl>,
+}
+
+/// 冲击方式
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShockKind {
+    /// 按绝对值的比例变动,如 -0.2 表示减少原值绝对值的 20%,
+    /// 负值随之恶化:净利润 -100 经 -0.2 冲击后为 -120
+    Relative,
+    /// 按绝对值变动,如资产负债率 10 表示上升 10 个百分点
+    Absolute,
+}
+
+/// 对单个字段的冲击,field 与 CompanyDataEn 字段名一致
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Shock {
+    pub field: String,
+    pub kind: ShockKind,
+    pub value: f64,
+    /// 适用的行业,为空时适用于全部行业
+    #[serde(default)]
+    pub industries: Vec<String>,
+}
+
+/// 压力情景,同一字段的多个冲击按顺序叠加
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StressScenario {
+    pub name: String,
+    pub shocks: Vec<Shock>,
+}
+
+/// 单家企业在压力情景下的评分变化
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StressedCompany {
+    pub company_id: String,
+    pub company_name: String,
+    pub industry: String,
+    pub base: ScoreSnapshot,
+    pub stressed: ScoreSnapshot,
+    /// 评级变动档数,负数为下调
+    pub notches: i32,
+    pub newly_rejected: bool,
+    /// 建议授信额度变化(万元)
+    pub limit_change: f64,
+}
+
+/// 单个压力情景的结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StressScenarioResult {
+    pub name: String,
+    /// 受冲击的企业数
+    pub affected: usize,
+    pub downgraded: usize,
+    pub upgraded: usize,
+    /// 压力下新增的拒绝授信企业数
+    pub newly_rejected: usize,
+    pub average_score_change: f64,
+    /// 建议授信总额(万元),需要担保和拒绝授信的企业不计额度
+    pub base_total_limit: f64,
+    pub stressed_total_limit: f64,
+    pub limit_change: f64,
+    /// 授信总额变化比例,基准总额为 0 时为 0
+    pub limit_change_ratio: f64,
+    /// 评级或额度发生变化的企业,下调幅度大的在前
+    pub companies: Vec<StressedCompany>,
+}
+
+/// 一次导入的压力测试结果
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StressTestResult {
+    pub import_id: i64,
+    pub scorecard: ScorecardId,
+    pub total_companies: usize,
+    pub scenarios: Vec<StressScenarioResult>,
+}
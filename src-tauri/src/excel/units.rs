@@ -0,0 +1,78 @@
+use crate::excel::consistency::NumericField;
+use crate::excel::types::{CompanyData, Unit};
+
+/// 金额列及对应字段,评分卡分档均按万元设定
+const MONETARY_COLUMNS: [(&str, NumericField); 10] = [
+    ("营业收入(万元)", NumericField::Revenue),
+    ("净利润(万元)", NumericField::NetProfit),
+    ("资产总额(万元)", NumericField::TotalAssets),
+    ("负债总额(万元)", NumericField::TotalLiabilities),
+    ("营运资金(万元)", NumericField::WorkingCapital),
+    ("留存收益(万元)", NumericField::RetainedEarnings),
+    ("息税前利润(万元)", NumericField::Ebit),
+    ("流动资产(万元)", NumericField::CurrentAssets),
+    ("流动负债(万元)", NumericField::CurrentLiabilities),
+    ("经营活动现金流量(万元)", NumericField::OperatingCashFlow),
+];
+
+impl Unit {
+    /// 将数值换算为万元
+    pub fn to_ten_thousand(self, value: f64) -> f64 {
+        match self {
+            Unit::Yuan => value / 10000.0,
+            Unit::Thousand => value / 10.0,
+            Unit::TenThousand => value,
+            Unit::HundredMillion => value * 10000.0,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Unit::Yuan => "元",
+            Unit::Thousand => "千元",
+            Unit::TenThousand => "万元",
+            Unit::HundredMillion => "亿元",
+        }
+    }
+
+    /// 识别规范化后的单位文本,如 "元"、"万元"、"yuan"、"100mcny"
+    pub fn parse(text: &str) -> Option<Unit> {
+        let text = text.trim_start_matches("单位:").trim_start_matches("单位");
+        let text = text.trim_end_matches("人民币");
+        match text {
+            "元" | "yuan" | "cny" | "rmb" => Some(Unit::Yuan),
+            "千元" | "千" | "thousand" | "kyuan" | "kcny" => Some(Unit::Thousand),
+            "万元" | "万" | "10kyuan" | "10kcny" => Some(Unit::TenThousand),
+            "亿元" | "亿" | "100myuan" | "100mcny" => Some(Unit::HundredMillion),
+            _ => None,
+        }
+    }
+}
+
+pub fn is_monetary(column: &str) -> bool {
+    MONETARY_COLUMNS.iter().any(|(name, _)| *name == column)
+}
+
+/// 从规范化列名末尾的括号中识别单位,返回 (去掉单位后的列名, 单位)
+pub fn split_unit(normalized: &str) -> Option<(&str, Unit)> {
+    let body = normalized.strip_suffix(')')?;
+    let open = body.rfind('(')?;
+    let unit = Unit::parse(&body[open + 1..])?;
+    Some((&body[..open], unit))
+}
+
+/// 将非万元单位的金额列换算为万元,并记录原始单位
+pub fn normalize_units(company: &mut CompanyData, units: &[(String, Unit)]) {
+    for (column, unit) in units {
+        if *unit == Unit::TenThousand {
+            continue;
+        }
+        let Some((_, field)) = MONETARY_COLUMNS.iter().find(|(name, _)| name == column) else {
+            continue;
+        };
+        if let Some(value) = field.get(company) {
+            field.set(company, unit.to_ten_thousand(value));
+        }
+        company.original_units.insert(column.clone(), *unit);
+    }
+}
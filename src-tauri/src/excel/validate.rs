@@ -0,0 +1,242 @@
+use crate::excel::cell::{CellValue, RowData};
+use crate::excel::template::INSTRUCTIONS_SHEET;
+use crate::excel::types::{CompanyData, Diagnostic, Severity};
+use std::collections::HashMap;
+
+const REQUIRED_TEXT: [&str; 3] = ["企业ID", "企业名称", "行业"];
+/// 必填数值列,布尔值表示是否为整数
+const REQUIRED_NUMBERS: [(&str, bool); 11] = [
+    ("营业收入(万元)", false),
+    ("净利润(万元)", false),
+    ("资产总额(万元)", false),
+    ("负债总额(万元)", false),
+    ("资产负债率(%)", false),
+    ("研发投入占比(%)", false),
+    ("专利数量", true),
+    ("上游核心企业数量", true),
+    ("下游客户数量", true),
+    ("历史逾期次数", true),
+    ("法律诉讼次数", true),
+];
+const OPTIONAL_NUMBERS: [&str; 6] = [
+    "营运资金(万元)",
+    "留存收益(万元)",
+    "息税前利润(万元)",
+    "流动资产(万元)",
+    "流动负债(万元)",
+    "经营活动现金流量(万元)",
+];
+/// 不允许为负数的列
+const NON_NEGATIVE: [&str; 8] = [
+    "资产总额(万元)",
+    "负债总额(万元)",
+    "研发投入占比(%)",
+    "专利数量",
+    "上游核心企业数量",
+    "下游客户数量",
+    "历史逾期次数",
+    "法律诉讼次数",
+];
+
+/// 逐行校验导入数据,收集诊断信息并识别跨工作表重复的企业ID
+#[derive(Default)]
+pub struct Validator {
+    seen: HashMap<(String, Option<i32>), String>,
+    errors: usize,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(
+        &mut self,
+        location: (&str, &str, usize),
+        column: Option<&str>,
+        severity: Severity,
+        message: String,
+    ) {
+        let (file, sheet_name, row) = location;
+        if severity == Severity::Error {
+            self.errors += 1;
+        }
+        self.diagnostics.push(Diagnostic {
+            file: file.to_string(),
+            sheet_name: sheet_name.to_string(),
+            row,
+            column: column.map(|c| c.to_string()),
+            severity,
+            message,
+        });
+    }
+
+    /// 检查表头,返回 false 时整个工作表不参与评分
+    pub fn check_headers(&mut self, file: &str, sheet_name: &str, headers: &[String]) -> bool {
+        let has = |name: &str| headers.iter().any(|h| h == name);
+        if !has("企业ID") && !has("企业名称") {
+            if sheet_name == INSTRUCTIONS_SHEET {
+                return false;
+            }
+            self.push(
+                (file, sheet_name, 1),
+                None,
+                Severity::Warning,
+                "未识别到企业数据列,已跳过该工作表".into(),
+            );
+            return false;
+        }
+
+        let missing = missing_columns(headers);
+        for name in &missing {
+            self.push(
+                (file, sheet_name, 1),
+                Some(name),
+                Severity::Error,
+                format!("缺少必填列 {}", name),
+            );
+        }
+        missing.is_empty()
+    }
+
+    /// 记录百分比列中按小数填写、已乘以 100 换算的单元格数,row 为其中第一个单元格所在行
+    pub fn note_rescaled(&mut self, location: (&str, &str, usize), column: &str, count: usize) {
+        self.push(
+            location,
+            Some(column),
+            Severity::Warning,
+            format!(
+                "{} 共 {} 个单元格按小数填写(百分比格式或列名标注小数),已乘以 100 换算为百分点",
+                column, count
+            ),
+        );
+    }
+
+    /// 记录百分比列中未换算、取值却在 0 到 1 之间的单元格数,row 为其中第一个单元格所在行
+    pub fn note_fraction_like(
+        &mut self,
+        location: (&str, &str, usize),
+        column: &str,
+        count: usize,
+    ) {
+        self.push(
+            location,
+            Some(column),
+            Severity::Warning,
+            format!(
+                "{} 共 {} 个单元格取值在 0 到 1 之间,如按小数填写请设置为百分比格式或在列名中标注(小数)",
+                column, count
+            ),
+        );
+    }
+
+    /// 校验单行数据,存在错误时返回 false,该行不参与评分
+    pub fn check_row(
+        &mut self,
+        location: (&str, &str, usize),
+        row_data: &RowData,
+        company: &CompanyData,
+    ) -> bool {
+        let errors_before = self.errors;
+        let empty = CellValue::Empty;
+        let cell = |name: &str| row_data.get(name).unwrap_or(&empty);
+
+        for name in REQUIRED_TEXT {
+            match cell(name) {
+                CellValue::Error(e) => self.push(
+                    location,
+                    Some(name),
+                    Severity::Error,
+                    format!("{} 单元格公式错误: {}", name, e),
+                ),
+                value if value.is_blank() => self.push(
+                    location,
+                    Some(name),
+                    Severity::Error,
+                    format!("{} 不能为空", name),
+                ),
+                _ => {}
+            }
+        }
+
+        for (name, integer) in REQUIRED_NUMBERS {
+            let value = cell(name);
+            if value.is_blank() {
+                self.push(
+                    location,
+                    Some(name),
+                    Severity::Warning,
+                    format!("{} 为空,按 0 计算", name),
+                );
+                continue;
+            }
+            let parsed = if integer {
+                value.as_integer().map(f64::from)
+            } else {
+                value.as_number()
+            };
+            match parsed {
+                None => {
+                    let message = invalid_number(name, value, integer);
+                    self.push(location, Some(name), Severity::Error, message);
+                }
+                Some(x) if x < 0.0 && NON_NEGATIVE.contains(&name) => self.push(
+                    location,
+                    Some(name),
+                    Severity::Error,
+                    format!("{} 不能为负数: {}", name, value.as_text()),
+                ),
+                _ => {}
+            }
+        }
+
+        for name in OPTIONAL_NUMBERS {
+            let value = cell(name);
+            if !value.is_blank() && value.as_number().is_none() {
+                let message = invalid_number(name, value, false);
+                self.push(location, Some(name), Severity::Error, message);
+            }
+        }
+
+        if !company.company_id.is_empty() {
+            let (file, sheet_name, row) = location;
+            let key = (company.company_id.clone(), company.fiscal_year);
+            let here = format!("{} / {} 第 {} 行", file, sheet_name, row);
+            if let Some(first) = self.seen.get(&key) {
+                let message = format!("企业ID {} 与 {} 重复", company.company_id, first);
+                self.push(location, Some("企业ID"), Severity::Error, message);
+            } else if self.errors == errors_before {
+                self.seen.insert(key, here);
+            }
+        }
+
+        self.errors == errors_before
+    }
+}
+
+/// 表头中缺少的必填列
+pub fn missing_columns(headers: &[String]) -> Vec<&'static str> {
+    REQUIRED_TEXT
+        .iter()
+        .copied()
+        .chain(REQUIRED_NUMBERS.iter().map(|(name, _)| *name))
+        .filter(|name| !headers.iter().any(|h| h == name))
+        .collect()
+}
+
+fn invalid_number(name: &str, value: &CellValue, integer: bool) -> String {
+    match value {
+        CellValue::Error(e) => format!("{} 单元格公式错误: {}", name, e),
+        CellValue::Date(..) => format!("{} 为日期格式,无法作为数值: {}", name, value.as_text()),
+        _ => format!("{} 无法解析为{}: {}", name, kind(integer), value.as_text()),
+    }
+}
+
+fn kind(integer: bool) -> &'static str {
+    if integer {
+        "整数"
+    } else {
+        "数值"
+    }
+}
@@ -4,6 +4,7 @@ mod solana;
 use ai::*;
 use excel::*;
 use solana::*;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -11,8 +12,19 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
         .manage(Wallet::default())
+        .setup(|app| {
+            let config_dir = app.path().app_config_dir()?;
+            init_scorecards(config_dir.join("scorecards"))?;
+            init_pd_model(config_dir.join("pd_model.json"))?;
+            init_consistency_rules(config_dir.join("consistency.json"))?;
+            init_header_mappings(config_dir.join("header_mappings.json"))?;
+            init_ecl_config(config_dir.join("ecl.json"))?;
+            init_store(app.path().app_data_dir()?.join("chaincredit.db"))?;
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             process_excel,
+            cancel_import,
             generate_template_excel,
             generate_single_report,
             set_private_key,
@@ -26,7 +38,35 @@ pub fn run() {
             get_company_by_id,
             submit_to_bank,
             get_bank_credit_limit,
-            call_deepseek_api_stream
+            get_bank_history,
+            list_imports,
+            load_import,
+            get_score_history,
+            get_rating_migration,
+            analyze_portfolio,
+            generate_portfolio_report,
+            calculate_ecl,
+            get_ecl_config,
+            save_ecl_config,
+            run_stress_test,
+            generate_stress_report,
+            call_deepseek_api_stream,
+            list_scorecards,
+            load_scorecard,
+            switch_scorecard,
+            get_scorecard,
+            rescore_company,
+            simulate_what_if,
+            analyze_rating_sensitivity,
+            fit_pd_model,
+            get_pd_calibration,
+            backtest_scorecard,
+            generate_backtest_report,
+            get_consistency_rules,
+            save_consistency_rules,
+            preview_header_mapping,
+            get_header_mappings,
+            save_header_mapping
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,6 +1,6 @@
 mod types;
 use anchor_client::{
-    solana_client::{rpc_config::RpcSendTransactionConfig, rpc_filter::RpcFilterType},
+    solana_client::rpc_config::RpcSendTransactionConfig,
     solana_sdk::{
         commitment_config::{CommitmentConfig, CommitmentLevel},
         pubkey::Pubkey,
@@ -10,7 +10,9 @@ use anchor_client::{
     Client, Cluster,
 };
 
-use credit::Company;
+use crate::excel::{Provenance, ScorecardId};
+use credit::{Company, ProvenanceRef, ScorecardRef};
+use std::path::Path;
 use std::sync::Arc;
 use tauri::State;
 pub use types::CompanyChainData;
@@ -20,6 +22,52 @@ pub fn get_company_pda(company_id: &str, program_id: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[b"company", company_id.as_bytes()], program_id)
 }
 
+/// 按字节截断,保证不截断多字节字符
+fn truncate_utf8(s: &str, max: usize) -> String {
+    let mut end = s.len().min(max);
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s[..end].to_string()
+}
+
+/// 链上只记录文件名,完整路径仅在本地有意义;原始文件可通过哈希核对
+fn to_provenance_ref(provenance: Option<&Provenance>) -> ProvenanceRef {
+    let Some(p) = provenance else {
+        return ProvenanceRef::default();
+    };
+    let file_name = Path::new(&p.file)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| p.file.clone());
+    ProvenanceRef {
+        file_name: truncate_utf8(&file_name, 128),
+        file_hash: p.file_hash.clone(),
+        sheet_name: truncate_utf8(&p.sheet_name, 96),
+        row: p.row as u32,
+        imported_at: p.imported_at,
+    }
+}
+
+/// 版本 0 的账户没有记录评分卡
+fn scorecard_from_chain(version: u8, scorecard: ScorecardRef) -> Option<ScorecardId> {
+    (version > 0).then(|| ScorecardId {
+        name: scorecard.name,
+        version: scorecard.version,
+        hash: scorecard.hash,
+    })
+}
+
+fn provenance_from_chain(provenance: ProvenanceRef) -> Option<Provenance> {
+    (!provenance.file_hash.is_empty()).then(|| Provenance {
+        file: provenance.file_name,
+        file_hash: provenance.file_hash,
+        sheet_name: provenance.sheet_name,
+        row: provenance.row as usize,
+        imported_at: provenance.imported_at,
+    })
+}
+
 #[tauri::command]
 pub async fn set_private_key(
     state: State<'_, Wallet>,
@@ -70,6 +118,8 @@ pub async fn initialize_company(
     credit_rating: String,
     credit_limit: String,
     risk_level: String,
+    scorecard: ScorecardId,
+    provenance: Option<Provenance>,
 ) -> Result<CompanyChainData, String> {
     let program_lock = state.program.lock().await;
     let program = program_lock
@@ -86,6 +136,12 @@ pub async fn initialize_company(
     let credit_rating_clone = credit_rating.clone();
     let credit_limit_clone = credit_limit.clone();
     let risk_level_clone = risk_level.clone();
+    let scorecard_ref = ScorecardRef {
+        name: scorecard.name.clone(),
+        version: scorecard.version.clone(),
+        hash: scorecard.hash.clone(),
+    };
+    let provenance_ref = to_provenance_ref(provenance.as_ref());
 
     tokio::task::spawn_blocking(move || {
         let tx_config = RpcSendTransactionConfig {
@@ -108,6 +164,8 @@ pub async fn initialize_company(
                 credit_rating: credit_rating_clone,
                 credit_limit: credit_limit_clone,
                 risk_level: risk_level_clone,
+                scorecard: scorecard_ref,
+                provenance: provenance_ref,
             })
             .send_with_spinner_and_config(tx_config)
     })
@@ -122,6 +180,8 @@ pub async fn initialize_company(
         credit_rating,
         credit_limit,
         risk_level,
+        scorecard: Some(scorecard),
+        provenance,
         authority: payer.to_string(),
         timestamp: 0,
     })
@@ -136,6 +196,8 @@ pub async fn update_company(
     credit_rating: String,
     credit_limit: String,
     risk_level: String,
+    scorecard: ScorecardId,
+    provenance: Option<Provenance>,
 ) -> Result<CompanyChainData, String> {
     let program_lock = state.program.lock().await;
     let program = program_lock
@@ -150,6 +212,12 @@ pub async fn update_company(
     let credit_rating_clone = credit_rating.clone();
     let credit_limit_clone = credit_limit.clone();
     let risk_level_clone = risk_level.clone();
+    let scorecard_ref = ScorecardRef {
+        name: scorecard.name.clone(),
+        version: scorecard.version.clone(),
+        hash: scorecard.hash.clone(),
+    };
+    let provenance_ref = to_provenance_ref(provenance.as_ref());
 
     tokio::task::spawn_blocking(move || {
         let tx_config = RpcSendTransactionConfig {
@@ -163,12 +231,15 @@ pub async fn update_company(
             .accounts(credit::accounts::UpdateCompany {
                 company: company_pda,
                 authority: payer,
+                system_program: anchor_client::solana_sdk::system_program::ID,
             })
             .args(credit::instruction::UpdateCompany {
                 credit_score,
                 credit_rating: credit_rating_clone,
                 credit_limit: credit_limit_clone,
                 risk_level: risk_level_clone,
+                scorecard: scorecard_ref,
+                provenance: provenance_ref,
             })
             .send_with_spinner_and_config(tx_config)
     })
@@ -186,6 +257,8 @@ pub async fn update_company(
         credit_rating,
         credit_limit,
         risk_level,
+        scorecard: Some(scorecard),
+        provenance,
         authority: payer.to_string(),
         timestamp: 0,
     })
@@ -239,25 +312,31 @@ pub async fn get_all_companies(state: State<'_, Wallet>) -> Result<Vec<CompanyCh
     drop(program_lock);
 
     let results = tokio::task::spawn_blocking(move || {
-        let filters: Vec<RpcFilterType> = vec![];
-
-        let accounts_with_pubkeys: Vec<(Pubkey, Company)> = program
-            .accounts::<Company>(filters)
+        // 旧版布局的账户无法直接按 Company 反序列化,读取原始数据后按版本解析
+        let accounts = program
+            .rpc()
+            .get_program_accounts(&program.id())
             .map_err(|e| e.to_string())?;
 
-        let results: Vec<CompanyChainData> = accounts_with_pubkeys
+        let results = accounts
             .into_iter()
-            .map(|(_pubkey, account)| CompanyChainData {
-                company_id: account.company_id,
-                company_name: account.company_name,
-                credit_score: account.credit_score,
-                credit_rating: account.credit_rating,
-                credit_limit: account.credit_limit,
-                risk_level: account.risk_level,
-                authority: account.authority.to_string(),
-                timestamp: account.timestamp,
+            .filter(|(_pubkey, account)| Company::is_company(&account.data))
+            .map(|(_pubkey, account)| {
+                let account = Company::load(&account.data).map_err(|e| e.to_string())?;
+                Ok(CompanyChainData {
+                    company_id: account.company_id,
+                    company_name: account.company_name,
+                    credit_score: account.credit_score,
+                    credit_rating: account.credit_rating,
+                    credit_limit: account.credit_limit,
+                    risk_level: account.risk_level,
+                    scorecard: scorecard_from_chain(account.version, account.scorecard),
+                    provenance: provenance_from_chain(account.provenance),
+                    authority: account.authority.to_string(),
+                    timestamp: account.timestamp,
+                })
             })
-            .collect();
+            .collect::<Result<Vec<CompanyChainData>, String>>()?;
 
         Ok::<Vec<CompanyChainData>, String>(results)
     })
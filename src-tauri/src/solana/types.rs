@@ -1,3 +1,4 @@
+use crate::excel::{Provenance, ScorecardId};
 use anchor_client::{
     solana_sdk::{pubkey::Pubkey, signature::Keypair},
     Program,
@@ -14,6 +15,9 @@ pub struct CompanyChainData {
     pub credit_rating: String,
     pub credit_limit: String,
     pub risk_level: String,
+    /// 旧版账户为 None
+    pub scorecard: Option<ScorecardId>,
+    pub provenance: Option<Provenance>,
     pub authority: String,
     pub timestamp: i64,
 }
@@ -19,7 +19,7 @@ import {
 import { toast } from "sonner";
 import { scrollbarStyles } from "../../components/DataVisualization/utils";
 
-import { CompanyChainData, SortOrder } from "./types";
+import { CompanyChainData, MANUAL_SCORECARD, SortOrder } from "./types";
 import StatsCards from "./StatsCards";
 import SearchBar from "./SearchBar";
 import CompanyTable from "./CompanyTable";
@@ -171,6 +171,7 @@ export default function Admin() {
     creditLimit: string,
     riskLevel: string
   ) => {
+    const current = companies.find(c => c.company_id === companyId);
     await invoke("update_company", {
       companyId,
       companyName,
@@ -178,6 +179,8 @@ export default function Admin() {
       creditRating,
       creditLimit,
       riskLevel,
+      scorecard: MANUAL_SCORECARD,
+      provenance: current?.provenance,
     });
     toast.success("企业信息更新成功");
     await fetchCompaniesData();
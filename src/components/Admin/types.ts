@@ -1,3 +1,5 @@
+import type { Provenance, ScorecardId } from "../DataVisualization/types";
+
 export interface CompanyChainData {
   company_id: string;
   company_name: string;
@@ -5,9 +7,15 @@ export interface CompanyChainData {
   credit_rating: string;
   credit_limit: string;
   risk_level: string;
+  // 旧版链上账户没有评分卡记录
+  scorecard: ScorecardId | null;
+  provenance: Provenance | null;
   timestamp?: number;
   authority: string;
 }
 
 export type SortField = keyof CompanyChainData;
 export type SortOrder = 'asc' | 'desc';
+
+// 管理员手动修改的评分不是由评分卡计算得出,链上以此标记
+export const MANUAL_SCORECARD: ScorecardId = { name: "manual", version: "", hash: "" };
@@ -0,0 +1,62 @@
+import { Card, CardContent, Typography, Box, Chip, Divider } from "@mui/material";
+import type { Company } from "./types";
+
+interface Props {
+  company: Company;
+}
+
+const round = (x: number) => Math.round(x * 100) / 100;
+
+export default function ScoreExplanationCard({ company }: Props) {
+  return (
+    <Card sx={{ mt: 2 }}>
+      <CardContent>
+        <Typography variant="h6" gutterBottom>评分解释</Typography>
+
+        {company.warnings.length > 0 && (
+          <>
+            <Typography variant="subtitle2" color="text.secondary" gutterBottom>数据一致性提示</Typography>
+            {company.warnings.map(warning => (
+              <Box key={warning.rule} sx={{ display: 'flex', alignItems: 'center', gap: 1, mb: 1 }}>
+                <Chip
+                  label={warning.corrected ? "已修正" : "待核实"}
+                  size="small"
+                  color={warning.corrected ? "info" : "warning"}
+                  variant="outlined"
+                />
+                <Typography variant="body2">{warning.message}</Typography>
+              </Box>
+            ))}
+            <Divider sx={{ my: 2 }} />
+          </>
+        )}
+
+        <Typography variant="subtitle2" color="text.secondary" gutterBottom>主要扣分原因</Typography>
+        {company.reason_codes.length === 0 ? (
+          <Typography variant="body2" color="success.main">各项因子均已取得满分</Typography>
+        ) : (
+          company.reason_codes.slice(0, 4).map(reason => (
+            <Box key={reason.code} sx={{ display: 'flex', alignItems: 'center', gap: 1, mb: 1 }}>
+              <Chip label={reason.code} size="small" color="warning" variant="outlined" />
+              <Typography variant="body2" sx={{ flexGrow: 1 }}>{reason.message}</Typography>
+              <Typography variant="body2" color="error">-{round(reason.points_lost)}</Typography>
+            </Box>
+          ))
+        )}
+
+        <Divider sx={{ my: 2 }} />
+
+        <Typography variant="subtitle2" color="text.secondary" gutterBottom>因子得分明细</Typography>
+        {company.factor_details.map(factor => (
+          <Box key={factor.key} sx={{ display: 'flex', justifyContent: 'space-between', mb: 1 }}>
+            <Typography variant="body2">{factor.category} · {factor.name}</Typography>
+            <Typography variant="body2" color="text.secondary">{factor.band}</Typography>
+            <Typography variant="body2" fontWeight="bold">
+              {round(factor.points)} / {round(factor.max_points)}
+            </Typography>
+          </Box>
+        ))}
+      </CardContent>
+    </Card>
+  );
+}
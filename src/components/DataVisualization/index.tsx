@@ -20,6 +20,7 @@ import ScoreChart from "./ScoreChart";
 import KeyMetricsCard from "./KeyMetricsCard";
 import RiskMetricsCard from "./RiskMetricsCard";
 import CreditStrategyCard from "./CreditStrategyCard";
+import ScoreExplanationCard from "./ScoreExplanationCard";
 
 import { scrollbarStyles } from "./utils";
 
@@ -101,6 +102,7 @@ export default function DataVisualization({ data }: Props) {
         creditRating: selectedCompany.credit_rating,
         creditLimit: selectedCompany.credit_limit,
         riskLevel: selectedCompany.risk_level,
+        scoredAt: selectedCompany.provenance?.imported_at ?? null,
       });
       setBankCreditLimit(limit);
       toast.success(`河北银行批复额度:${limit} 万元`);
@@ -175,6 +177,8 @@ export default function DataVisualization({ data }: Props) {
         creditRating: selectedCompany.credit_rating,
         creditLimit: selectedCompany.credit_limit,
         riskLevel: selectedCompany.risk_level,
+        scorecard: selectedCompany.scorecard,
+        provenance: selectedCompany.provenance,
       });
 
       toast.success("上链成功");
@@ -273,6 +277,8 @@ export default function DataVisualization({ data }: Props) {
               <RiskMetricsCard company={selectedCompany} />
               <CreditStrategyCard company={selectedCompany} />
             </Box>
+
+            <ScoreExplanationCard company={selectedCompany} />
           </>
         )}
       </Box>
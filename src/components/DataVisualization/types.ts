@@ -13,6 +13,34 @@ export interface CompanyData {
   downstream_customers: number;
   overdue_count: number;
   legal_disputes_count: number;
+  working_capital?: number | null;
+  retained_earnings?: number | null;
+  ebit?: number | null;
+  current_assets?: number | null;
+  current_liabilities?: number | null;
+  operating_cash_flow?: number | null;
+  fiscal_year?: number | null;
+  history?: FinancialPeriod[];
+  original_units?: Record<string, Unit>;
+}
+
+export interface FinancialPeriod {
+  fiscal_year: number;
+  revenue: number;
+  net_profit: number;
+  total_assets: number;
+  total_liabilities: number;
+  debt_to_asset_ratio: number;
+  r_and_d_ratio: number;
+}
+
+export interface DistressIndicators {
+  altman_z: number | null;
+  altman_zone: string | null;
+  ohlson_o: number | null;
+  ohlson_probability: number | null;
+  zmijewski: number | null;
+  zmijewski_probability: number | null;
 }
 
 export interface ScoreDetails {
@@ -21,6 +49,30 @@ export interface ScoreDetails {
   supply_chain_score: number;
   risk_score: number;
   industry_adjustment: number;
+  trend_adjustment: number;
+}
+
+export interface FactorContribution {
+  key: string;
+  name: string;
+  category: string;
+  value: number | string | null;
+  band: string;
+  points: number;
+  max_points: number;
+}
+
+export interface ReasonCode {
+  code: string;
+  factor: string;
+  message: string;
+  points_lost: number;
+}
+
+export interface ScorecardId {
+  name: string;
+  version: string;
+  hash: string;
 }
 
 export interface Company {
@@ -30,11 +82,109 @@ export interface Company {
   credit_limit: string;
   risk_level: string;
   score_details: ScoreDetails;
+  factor_details: FactorContribution[];
+  reason_codes: ReasonCode[];
+  scorecard: ScorecardId;
+  probability_of_default: number | null;
+  distress: DistressIndicators;
+  warnings: ConsistencyWarning[];
+  provenance: Provenance | null;
+}
+
+export interface Provenance {
+  file: string;
+  file_hash: string;
+  sheet_name: string;
+  row: number;
+  imported_at: number;
+}
+
+export interface ConsistencyWarning {
+  rule: string;
+  field: string;
+  message: string;
+  original: number;
+  expected: number;
+  corrected: boolean;
+}
+
+export interface PeerPercentile {
+  metric: string;
+  metric_name: string;
+  value: number;
+  percentile: number;
+  peer_median: number;
+}
+
+export interface PeerStats {
+  company_id: string;
+  industry: string;
+  peer_count: number;
+  percentiles: PeerPercentile[];
 }
 
 export interface ExcelResult {
   file: string;
   sheet_name: string;
   total_companies: number;
+  scorecard: ScorecardId;
   companies: Company[];
+  peer_stats: PeerStats[];
+}
+
+export interface Diagnostic {
+  file: string;
+  sheet_name: string;
+  row: number;
+  column: string | null;
+  severity: "error" | "warning";
+  message: string;
+}
+
+export interface ImportProgress {
+  stage: "reading" | "scoring";
+  files_done: number;
+  files_total: number;
+  rows_read: number;
+  rows_scored: number;
+  rows_total: number;
+}
+
+export interface ImportResult {
+  import_id: number | null;
+  results: ExcelResult[];
+  diagnostics: Diagnostic[];
+}
+
+export interface ImportRecord {
+  id: number;
+  imported_at: number;
+  source: string | null;
+  scorecard: ScorecardId;
+  files: string[];
+  total_companies: number;
+}
+
+export type MatchSource = "exact" | "alias" | "user" | "duplicate" | "unmatched";
+
+export type Unit = "元" | "千元" | "万元" | "亿元";
+
+export interface ColumnMapping {
+  index: number;
+  header: string;
+  field: string | null;
+  matched_by: MatchSource;
+  unit: Unit | null;
+}
+
+export interface SourceMapping {
+  columns: Record<string, string>;
+  units: Record<string, Unit>;
+}
+
+export interface SheetMapping {
+  file: string;
+  sheet_name: string;
+  columns: ColumnMapping[];
+  missing: string[];
 }
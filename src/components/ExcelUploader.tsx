@@ -116,7 +116,7 @@ export default function ExcelUploader({ onFileSelect, disabled = false }: ExcelU
 
   const isExcelFile = (filePath: string) => {
     const ext = filePath.toLowerCase();
-    return [".xlsx", ".xls", ".xlsm", ".xlsb"].some((suffix) =>
+    return [".xlsx", ".xls", ".xlsm", ".xlsb", ".csv", ".json", ".jsonl"].some((suffix) =>
       ext.endsWith(suffix)
     );
   };
@@ -126,7 +126,8 @@ export default function ExcelUploader({ onFileSelect, disabled = false }: ExcelU
     const selected = await open({
       multiple: true,
       filters: [
-        { name: "Excel", extensions: ["xlsx", "xls", "xlsm", "xlsb"] }
+        { name: "Excel", extensions: ["xlsx", "xls", "xlsm", "xlsb"] },
+        { name: "CSV / JSON", extensions: ["csv", "json", "jsonl"] }
       ]
     });
     if (!selected) return;
@@ -202,7 +203,13 @@ export default function ExcelUploader({ onFileSelect, disabled = false }: ExcelU
           '上游核心企业数量',
           '下游客户数量',
           '历史逾期次数',
-          '法律诉讼次数'
+          '法律诉讼次数',
+          '营运资金(万元)',
+          '留存收益(万元)',
+          '息税前利润(万元)',
+          '流动资产(万元)',
+          '流动负债(万元)',
+          '经营活动现金流量(万元)'
         ],
         templateRow: [
           'QY006',
@@ -219,6 +226,12 @@ export default function ExcelUploader({ onFileSelect, disabled = false }: ExcelU
           '110',
           '0',
           '0',
+          '9000',
+          '12000',
+          '3200',
+          '18000',
+          '9000',
+          '2800',
         ]
       });
       toast.success('生成模板文件成功!');
@@ -266,7 +279,7 @@ export default function ExcelUploader({ onFileSelect, disabled = false }: ExcelU
         <input
           ref={fileInputRef}
           type="file"
-          accept=".xlsx,.xls,.xlsm,.xlsb"
+          accept=".xlsx,.xls,.xlsm,.xlsb,.csv,.json,.jsonl"
           onChange={handleFileSelect}
           style={{ display: "none" }}
         />
@@ -373,7 +386,7 @@ export default function ExcelUploader({ onFileSelect, disabled = false }: ExcelU
                 letterSpacing: 0.5,
               }}
             >
-              .xlsx .xls .xlsm .xlsb
+              .xlsx .xls .xlsm .xlsb .csv .json .jsonl
             </Typography>
           </Box>
         </Box>
@@ -1,17 +1,21 @@
 import { useState, useEffect } from "react";
 import { useNavigate } from "react-router";
-import { Box, Typography, CircularProgress } from "@mui/material";
+import { Box, Typography, CircularProgress, LinearProgress, Button } from "@mui/material";
 import ExcelUploader from "../components/ExcelUploader";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { toast } from "sonner";
 import { useAtom } from "jotai";
-import { DataAtom, titleAtom } from "../utils/store";
-import { ExcelResult } from "../components/DataVisualization/types";
+import { DataAtom, DiagnosticsAtom, titleAtom } from "../utils/store";
+import { ExcelResult, ImportProgress, ImportRecord, ImportResult } from "../components/DataVisualization/types";
 
 export default function Upload() {
   const [_title, setTitle] = useAtom(titleAtom);
   const [isProcessing, setIsProcessing] = useState(false);
+  const [progress, setProgress] = useState<ImportProgress | null>(null);
+  const [lastImport, setLastImport] = useState<ImportRecord | null>(null);
   const [_processedData, setProcessedData] = useAtom(DataAtom);
+  const [_diagnostics, setDiagnostics] = useAtom(DiagnosticsAtom);
   const navigate = useNavigate();
 
   useEffect(() => {
@@ -19,22 +23,67 @@ export default function Upload() {
     return () => setTitle("");
   }, [setTitle]);
 
+  useEffect(() => {
+    const unlisten = listen<ImportProgress>("import_progress", event => setProgress(event.payload));
+    return () => {
+      unlisten.then(f => f());
+    };
+  }, []);
+
+  useEffect(() => {
+    invoke<ImportRecord[]>("list_imports")
+      .then(imports => setLastImport(imports[0] ?? null))
+      .catch(() => setLastImport(null));
+  }, []);
+
+  const handleRestore = async () => {
+    if (!lastImport) return;
+    try {
+      const results: ExcelResult[] = await invoke("load_import", { importId: lastImport.id });
+      setDiagnostics([]);
+      setProcessedData(results);
+      navigate("/dashboard");
+      toast.success(`已恢复 ${lastImport.total_companies} 家企业的评分结果`);
+    } catch (err) {
+      toast.error(String(err));
+    }
+  };
+
+  const handleCancel = async () => {
+    await invoke("cancel_import");
+  };
+
   const handleFileSelect = async (filePath: string[]) => {
     setIsProcessing(true);
+    setProgress(null);
     setProcessedData([]);
+    setDiagnostics([]);
     try {
-      const res: ExcelResult[] = await invoke("process_excel", { paths: filePath });
-      if (res.length === 0) {
-        toast.error("处理失败,返回数据为空或无效");
+      const res: ImportResult = await invoke("process_excel", { paths: filePath });
+      setDiagnostics(res.diagnostics);
+      const errors = res.diagnostics.filter(d => d.severity === "error");
+      if (res.results.length === 0) {
+        toast.error(errors.length > 0
+          ? `处理失败:${errors[0].sheet_name} 第 ${errors[0].row} 行 ${errors[0].message}`
+          : "处理失败,返回数据为空或无效");
         return;
       }
-      setProcessedData(res);
+      setProcessedData(res.results);
       navigate("/dashboard");
-      toast.success("文件处理成功！");
+      if (errors.length > 0) {
+        toast.warning(`文件处理完成,${errors.length} 处数据校验未通过,相关行未参与评分`);
+      } else {
+        toast.success("文件处理成功！");
+      }
     } catch (err) {
-      toast.error(String(err));
+      if (String(err) === "导入已取消") {
+        toast.info("已取消导入");
+      } else {
+        toast.error(String(err));
+      }
     } finally {
       setIsProcessing(false);
+      setProgress(null);
     }
   };
 
@@ -42,11 +91,36 @@ export default function Upload() {
     <Box sx={{ height: "100%", display: "flex", alignItems: "center", justifyContent: "center", p: 3 }}>
       {isProcessing ? (
         <Box sx={{ display: "flex", flexDirection: "column", alignItems: "center", gap: 2 }}>
-          <CircularProgress size={60} />
-          <Typography variant="h6" color="text.secondary">正在处理文件...</Typography>
+          {progress?.stage === "scoring" && progress.rows_total > 0 ? (
+            <Box sx={{ width: 360 }}>
+              <LinearProgress
+                variant="determinate"
+                value={(progress.rows_scored / progress.rows_total) * 100}
+              />
+            </Box>
+          ) : (
+            <CircularProgress size={60} />
+          )}
+          <Typography variant="h6" color="text.secondary">
+            {progress === null
+              ? "正在处理文件..."
+              : progress.stage === "reading"
+                ? `正在读取文件 ${progress.files_done}/${progress.files_total},已读取 ${progress.rows_read} 行`
+                : `正在评分 ${progress.rows_scored}/${progress.rows_total} 家企业,已完成 ${progress.files_done}/${progress.files_total} 个文件`}
+          </Typography>
+          <Button variant="outlined" color="inherit" onClick={handleCancel}>
+            取消
+          </Button>
         </Box>
       ) : (
-        <ExcelUploader onFileSelect={handleFileSelect} disabled={isProcessing} />
+        <Box sx={{ display: "flex", flexDirection: "column", alignItems: "center", gap: 2 }}>
+          <ExcelUploader onFileSelect={handleFileSelect} disabled={isProcessing} />
+          {lastImport && (
+            <Button variant="text" onClick={handleRestore}>
+              {`恢复最近一次导入(${new Date(lastImport.imported_at * 1000).toLocaleString()},${lastImport.total_companies} 家企业)`}
+            </Button>
+          )}
+        </Box>
       )}
     </Box>
   );
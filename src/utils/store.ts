@@ -1,4 +1,6 @@
 import { atom } from 'jotai';
+import type { Diagnostic } from '../components/DataVisualization/types';
 
 export const DataAtom = atom<any[] | null>(null);
+export const DiagnosticsAtom = atom<Diagnostic[]>([]);
 export const titleAtom = atom<string>("");